@@ -1,18 +1,23 @@
 use leptos::prelude::*;
 use leptos_meta::{provide_meta_context, MetaTags, Stylesheet, Title};
 use leptos_router::{
-    components::{Route, Router, Routes, A},
+    components::{ParentRoute, Route, Router, Routes, A},
     path, StaticSegment,
 };
 use thaw::{ssr::SSRMountStyleProvider, ConfigProvider, ToasterProvider};
 
 use crate::components::add_card::AddCard;
 use crate::components::edit_card::EditCard;
+use crate::components::generate_cards::GenerateCards;
 use crate::components::list_cards::ListCards;
+use crate::components::list_duplicates::ListDuplicates;
 use crate::components::review_by_tag::ReviewByTag;
 use crate::components::review_cards::ReviewAllCards;
 use crate::components::view_card::ViewCard;
-use crate::languages::components::{GenerateSentence, Overview, Vocabulary, WriteStory};
+use crate::languages::components::{
+    ConjugationDrills, GenerateSentence, LanguagesLayout, Library, Listening, Overview, ReadText,
+    Vocabulary, WriteStory,
+};
 
 pub fn shell(options: LeptosOptions) -> impl IntoView {
     view! {
@@ -65,6 +70,11 @@ fn AppRouter() -> impl IntoView {
                                 "Add new card"
                             </div>
                         </A>
+                        <A href="/generate-cards">
+                            <div class="block px-4 py-2 text-gray-700 hover:bg-gray-200 rounded">
+                                "Generate cards"
+                            </div>
+                        </A>
                         <A href="/review-cards">
                             <div class="block px-4 py-2 text-gray-700 hover:bg-gray-200 rounded">
                                 "Review cards"
@@ -75,6 +85,11 @@ fn AppRouter() -> impl IntoView {
                                 "List cards"
                             </div>
                         </A>
+                        <A href="/duplicates">
+                            <div class="block px-4 py-2 text-gray-700 hover:bg-gray-200 rounded">
+                                "Duplicates"
+                            </div>
+                        </A>
                         <A href="/learn-languages">
                             <div class="block px-4 py-2 text-gray-700 hover:bg-gray-200 rounded">
                                 "Learn languages"
@@ -86,18 +101,23 @@ fn AppRouter() -> impl IntoView {
                     <Routes fallback=|| "Page not found.".into_view()>
                         <Route path=StaticSegment("") view=AddCard />
                         <Route path=path!("/add-card") view=AddCard />
+                        <Route path=path!("/generate-cards") view=GenerateCards />
                         <Route path=path!("/review-cards") view=ReviewAllCards />
                         <Route path=path!("/review-cards/:tag") view=ReviewByTag />
                         <Route path=path!("/list-cards") view=ListCards />
+                        <Route path=path!("/duplicates") view=ListDuplicates />
                         <Route path=path!("/cards/edit/:id") view=EditCard />
                         <Route path=path!("/cards/:id") view=ViewCard />
-                        <Route path=path!("/learn-languages") view=Overview />
-                        <Route path=path!("/learn-languages/vocabulary") view=Vocabulary />
-                        <Route
-                            path=path!("/learn-languages/generate-sentence")
-                            view=GenerateSentence
-                        />
-                        <Route path=path!("/learn-languages/write-story") view=WriteStory />
+                        <ParentRoute path=path!("/learn-languages") view=LanguagesLayout>
+                            <Route path=path!("") view=Overview />
+                            <Route path=path!("vocabulary") view=Vocabulary />
+                            <Route path=path!("generate-sentence") view=GenerateSentence />
+                            <Route path=path!("write-story") view=WriteStory />
+                            <Route path=path!("library") view=Library />
+                            <Route path=path!("read") view=ReadText />
+                            <Route path=path!("conjugation") view=ConjugationDrills />
+                            <Route path=path!("listen") view=Listening />
+                        </ParentRoute>
                     </Routes>
                 </main>
             </div>
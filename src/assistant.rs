@@ -0,0 +1,345 @@
+//! AI help with flashcards of any subject: unlike [crate::languages::ai::Agent], it doesn't
+//! depend on the language we are learning.
+
+#![cfg(feature = "ssr")]
+
+use crate::errors::AppError;
+use crate::llm::{parse_xml_tags, prompt_parsed, require_xml_tag, LlmResponse, Provider};
+use crate::model::{AnswerGrade, CardSuggestion, Flashcard};
+
+static GEN_CARDS_PROMPT: &str = "
+Here are my notes:
+
+<notes>
+{notes}
+</notes>
+
+Create flashcards that help me remember the key facts and concepts from these notes.
+Each answer should be short: a sentence or two, a command, a formula, etc.
+Don't repeat the same fact in multiple cards.
+Use Markdown when it helps, e.g. for code.
+Output in this format:
+
+<card>
+<question>
+What does `git rebase` do?
+</question>
+<answer>
+Reapplies the commits of the current branch on top of another commit.
+</answer>
+</card>
+<card>
+...
+</card>
+";
+
+static GRADE_ANSWER_PROMPT: &str = "
+I'm reviewing a flashcard. Here is the question:
+
+<question>
+{question}
+</question>
+
+The correct answer:
+
+<correct_answer>
+{answer}
+</correct_answer>
+
+My answer:
+
+<my_answer>
+{my_answer}
+</my_answer>
+
+Grade my answer with one of these grades:
+* again - wrong or missing the main point;
+* hard - partially correct, important details are missing;
+* good - correct, maybe with minor omissions;
+* easy - complete and correct.
+Judge the meaning, not the wording. Explain in a sentence or two what was missing or wrong.
+Output in this format:
+
+<grade>
+good
+</grade>
+<explanation>
+...
+</explanation>
+";
+
+static IMPROVE_CARD_PROMPT: &str = "
+Here is a flashcard I use to learn:
+
+<question>
+{question}
+</question>
+<answer>
+{answer}
+</answer>
+<examples>
+{examples}
+</examples>
+
+Improve it:
+* make the question clear and unambiguous;
+* make the answer as short as possible, while keeping it correct;
+* write 2-3 short examples that show the answer in use, as a Markdown list.
+Keep the language and the Markdown formatting of the card.
+If a field is good already, repeat it unchanged.
+Output in this format:
+
+<question>
+...
+</question>
+<answer>
+...
+</answer>
+<examples>
+...
+</examples>
+";
+
+static DISTRACTORS_PROMPT: &str = "
+I'm reviewing a flashcard as a multiple-choice question. Here is the question:
+
+<question>
+{question}
+</question>
+
+The correct answer:
+
+<answer>
+{answer}
+</answer>
+
+Write {count} wrong answers to pick from, besides the correct one and these:
+
+<other_answers>
+{other_answers}
+</other_answers>
+
+Each wrong answer must be plausible, clearly wrong for someone who knows the answer,
+and of the same kind, length and language as the correct one.
+Output each in its own tag:
+
+<distractor>
+...
+</distractor>
+<distractor>
+...
+</distractor>
+";
+
+pub struct Assistant {
+    llm: Provider,
+}
+
+impl Assistant {
+    pub fn from_settings() -> Self {
+        Self::new(Provider::from_settings())
+    }
+
+    pub fn new(llm: Provider) -> Self {
+        Self { llm }
+    }
+
+    /// Drafts flashcards from the notes. They are not saved.
+    pub async fn generate_cards(&self, notes: &str) -> Result<Vec<Flashcard>, AppError> {
+        let prompt = GEN_CARDS_PROMPT.replace("{notes}", notes);
+        let CardDrafts(cards) = prompt_parsed(&self.llm, &prompt).await?;
+        Ok(cards)
+    }
+
+    /// Grades the typed answer by comparing it with the card's answer.
+    pub async fn grade_answer(
+        &self,
+        card: &Flashcard,
+        my_answer: &str,
+    ) -> Result<AnswerGrade, AppError> {
+        let prompt = GRADE_ANSWER_PROMPT
+            .replace("{question}", &card.question)
+            .replace("{answer}", &card.answer)
+            .replace("{my_answer}", my_answer);
+        prompt_parsed(&self.llm, &prompt).await
+    }
+
+    /// Suggests a clearer question, a shorter answer and examples for the card.
+    pub async fn improve_card(&self, card: &Flashcard) -> Result<CardSuggestion, AppError> {
+        let prompt = IMPROVE_CARD_PROMPT
+            .replace("{question}", &card.question)
+            .replace("{answer}", &card.answer)
+            .replace("{examples}", card.examples.as_deref().unwrap_or_default());
+        prompt_parsed(&self.llm, &prompt).await
+    }
+
+    /// Wrong answers for a multiple-choice review of the card, different from `other_answers`.
+    pub async fn distractors(
+        &self,
+        card: &Flashcard,
+        count: usize,
+        other_answers: &[String],
+    ) -> Result<Vec<String>, AppError> {
+        let prompt = DISTRACTORS_PROMPT
+            .replace("{question}", &card.question)
+            .replace("{answer}", &card.answer)
+            .replace("{count}", &count.to_string())
+            .replace("{other_answers}", &other_answers.join("\n"));
+        let Distractors(mut distractors) = prompt_parsed(&self.llm, &prompt).await?;
+        distractors.retain(|d| *d != card.answer && !other_answers.contains(d));
+        distractors.truncate(count);
+        Ok(distractors)
+    }
+}
+
+struct CardDrafts(Vec<Flashcard>);
+
+impl LlmResponse for CardDrafts {
+    const FORMAT: &'static str = "<card>
+<question>
+...
+</question>
+<answer>
+...
+</answer>
+</card>";
+
+    fn parse(response: &str) -> Result<Self, AppError> {
+        let cards = parse_xml_tags(response, "card")
+            .iter()
+            .map(|card| {
+                Ok(Flashcard::new(
+                    require_xml_tag(card, "question")?,
+                    require_xml_tag(card, "answer")?,
+                ))
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+        if cards.is_empty() {
+            return Err(AppError::LlmError("<card> is missing".to_string()));
+        }
+        Ok(CardDrafts(cards))
+    }
+}
+
+impl LlmResponse for AnswerGrade {
+    const FORMAT: &'static str = "<grade>
+again, hard, good or easy
+</grade>
+<explanation>
+...
+</explanation>";
+
+    fn parse(response: &str) -> Result<Self, AppError> {
+        Ok(AnswerGrade {
+            grade: require_xml_tag(response, "grade")?
+                .parse()
+                .map_err(AppError::LlmError)?,
+            explanation: require_xml_tag(response, "explanation")?,
+        })
+    }
+}
+
+impl LlmResponse for CardSuggestion {
+    const FORMAT: &'static str = "<question>
+...
+</question>
+<answer>
+...
+</answer>
+<examples>
+...
+</examples>";
+
+    fn parse(response: &str) -> Result<Self, AppError> {
+        Ok(CardSuggestion {
+            question: require_xml_tag(response, "question")?,
+            answer: require_xml_tag(response, "answer")?,
+            examples: require_xml_tag(response, "examples")?,
+        })
+    }
+}
+
+struct Distractors(Vec<String>);
+
+impl LlmResponse for Distractors {
+    const FORMAT: &'static str = "<distractor>
+...
+</distractor>
+<distractor>
+...
+</distractor>";
+
+    fn parse(response: &str) -> Result<Self, AppError> {
+        let distractors = parse_xml_tags(response, "distractor");
+        if distractors.is_empty() {
+            return Err(AppError::LlmError("<distractor> is missing".to_string()));
+        }
+        Ok(Distractors(distractors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::MockProvider;
+    use crate::model::Grade;
+
+    #[test]
+    fn test_parse_card_drafts() {
+        let CardDrafts(cards) = CardDrafts::parse(include_str!("fixtures/cards.txt")).unwrap();
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].question, "What does `git stash` do?");
+        assert_eq!(cards[1].answer, "`git stash pop`");
+    }
+
+    #[tokio::test]
+    async fn test_grade_answer() {
+        let assistant = Assistant::new(Provider::Mock(MockProvider::new([
+            "<grade>great</grade><explanation>Correct.</explanation>",
+            include_str!("fixtures/answer_grade.txt"),
+        ])));
+        let card = Flashcard::new(
+            "What does `git stash pop` do?".to_string(),
+            "Applies the latest stash and drops it.".to_string(),
+        );
+
+        let grade = assistant
+            .grade_answer(&card, "Applies the stashed changes")
+            .await
+            .unwrap();
+
+        assert_eq!(grade.grade, Grade::Hard);
+        assert_eq!(
+            grade.explanation,
+            "The stash is also removed from the stash list."
+        );
+    }
+
+    #[test]
+    fn test_parse_card_suggestion() {
+        let suggestion =
+            CardSuggestion::parse(include_str!("fixtures/card_suggestion.txt")).unwrap();
+        assert_eq!(suggestion.question, "What does `git stash pop` do?");
+        assert!(suggestion.examples.starts_with("* `git stash pop`"));
+    }
+
+    #[tokio::test]
+    async fn test_distractors() {
+        let assistant = Assistant::new(Provider::Mock(MockProvider::new([
+            "No options here.",
+            "<distractor>git stash drop</distractor>\n<distractor>git stash apply</distractor>\n\
+             <distractor>git stash pop</distractor>\n<distractor>git stash list</distractor>",
+        ])));
+        let card = Flashcard::new(
+            "How to apply the latest stash and drop it?".to_string(),
+            "git stash pop".to_string(),
+        );
+
+        let distractors = assistant
+            .distractors(&card, 2, &["git stash drop".to_string()])
+            .await
+            .unwrap();
+
+        assert_eq!(distractors, vec!["git stash apply", "git stash list"]);
+    }
+}
@@ -0,0 +1,120 @@
+//! Answer options for multiple-choice reviews, and the check of typed answers.
+//!
+//! Wrong options (distractors) are the answers of other cards with the same tag, the most
+//! similar to the right answer first, so that the right one can't be guessed by its look.
+
+use std::collections::HashSet;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::duplicates::{normalize, similarity};
+use crate::model::{ChoiceSet, Flashcard, Grade};
+
+/// Options shown for a card, the right answer included.
+pub const CHOICES: usize = 4;
+
+/// Answers of the cards sharing a tag with the card, the most similar to its answer first.
+/// Answers that are the same as the right one, once normalized, are skipped.
+pub fn distractors(card: &Flashcard, cards: &[Flashcard], count: usize) -> Vec<String> {
+    let mut seen = HashSet::from([normalize(&card.answer)]);
+    let mut candidates = cards
+        .iter()
+        .filter(|other| other.id != card.id && other.tags.iter().any(|t| card.tags.contains(t)))
+        .map(|other| (similarity(&card.answer, &other.answer), other))
+        .collect::<Vec<_>>();
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.id.cmp(&b.1.id)));
+    candidates
+        .into_iter()
+        .map(|(_, other)| other.answer.clone())
+        .filter(|answer| seen.insert(normalize(answer)))
+        .take(count)
+        .collect()
+}
+
+/// The right answer among the distractors, shuffled anew for each review: the right answer
+/// can't be remembered by its position.
+pub fn choice_set(card: &Flashcard, distractors: Vec<String>, rng: &mut impl Rng) -> ChoiceSet {
+    let mut options = distractors;
+    options.push(card.answer.clone());
+    options.shuffle(rng);
+    ChoiceSet {
+        card_id: card.id,
+        options,
+    }
+}
+
+/// "Good" if the typed answer is the answer, whatever the case, spacing and punctuation,
+/// "again" otherwise. Accents count: "hablais" isn't "habláis".
+pub fn typed_answer_grade(typed: &str, answer: &str) -> Grade {
+    if normalize(typed) == normalize(answer) {
+        Grade::Good
+    } else {
+        Grade::Again
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn card(id: i64, answer: &str, tag: &str) -> Flashcard {
+        let mut card = Flashcard::new(format!("Question {}", id), answer.to_string());
+        card.id = id;
+        card.tags = vec![tag.to_string()];
+        card
+    }
+
+    #[test]
+    fn test_choice_set() {
+        let cards = vec![
+            card(1, "git stash pop", "git"),
+            card(2, "git stash drop", "git"),
+            card(3, "git rebase", "git"),
+            card(4, "Git stash pop!", "git"),
+            card(5, "git stash list", "rust"),
+            card(6, "git commit", "git"),
+            card(7, "git stash apply", "git"),
+        ];
+
+        let wrong = distractors(&cards[0], &cards, CHOICES - 1);
+        assert_eq!(
+            wrong,
+            vec!["git stash drop", "git stash apply", "git commit"]
+        );
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let set = choice_set(&cards[0], wrong.clone(), &mut rng);
+        assert_eq!(set.card_id, 1);
+        assert_eq!(set.options.len(), CHOICES);
+        assert_eq!(
+            set.options
+                .iter()
+                .filter(|option| **option == "git stash pop")
+                .count(),
+            1
+        );
+        // The right answer moves from one review to another.
+        let positions = (0..20)
+            .map(|_| {
+                choice_set(&cards[0], wrong.clone(), &mut rng)
+                    .options
+                    .iter()
+                    .position(|option| option == "git stash pop")
+                    .unwrap()
+            })
+            .collect::<HashSet<_>>();
+        assert!(positions.len() > 1);
+        assert!(distractors(&cards[4], &cards, CHOICES - 1).is_empty());
+    }
+
+    #[test]
+    fn test_typed_answer_grade() {
+        assert_eq!(typed_answer_grade(" Habláis. ", "habláis"), Grade::Good);
+        assert_eq!(typed_answer_grade("he  hablado", "He hablado"), Grade::Good);
+        assert_eq!(typed_answer_grade("hablais", "habláis"), Grade::Again);
+        assert_eq!(typed_answer_grade("", "habláis"), Grade::Again);
+    }
+}
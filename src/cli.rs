@@ -1,47 +1,336 @@
 //! Usage:
 //! cargo run --bin=cli --features=ssr
+//! cargo run --bin=cli --features=ssr -- review --tag spanish
+//! cargo run --bin=cli --features=ssr -- add --question "..." --answer "..." --tag rust
+//! cargo run --bin=cli --features=ssr -- help
 
+use clap::{Parser, Subcommand};
 use flashcard_app::db::Database;
+use flashcard_app::duplicates::{find_duplicates, SIMILARITY_THRESHOLD};
+use flashcard_app::model::Flashcard;
+use flashcard_app::settings::Settings;
+use flashcard_app::tui;
+use serde::{Deserialize, Serialize};
 use std::env;
 use std::error::Error;
-use std::io;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
 
-static DB_DIR: &str = "db";
+#[derive(Parser)]
+#[command(about = "Review and manage flashcards in the terminal")]
+struct Cli {
+    /// Reviews the due cards when omitted.
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Review the due cards.
+    Review {
+        #[arg(long)]
+        tag: Option<String>,
+    },
+    /// Add a card. Opens $EDITOR unless both the question and the answer are given.
+    Add {
+        #[arg(short, long)]
+        question: Option<String>,
+        #[arg(short, long)]
+        answer: Option<String>,
+        #[arg(short, long)]
+        examples: Option<String>,
+        #[arg(short, long)]
+        source: Option<String>,
+        /// Can be repeated.
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+    },
+    /// Edit a card in $EDITOR.
+    Edit { id: i64 },
+    /// List the cards.
+    List {
+        #[arg(long)]
+        tag: Option<String>,
+    },
+    /// Print a card.
+    Show { id: i64 },
+    /// Delete a card with its review history.
+    Delete {
+        id: i64,
+        /// Don't ask for confirmation.
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// List the tags with the number of cards.
+    Tags,
+    /// Print review statistics.
+    Stats,
+    /// Add the cards from a file created by `export`.
+    Import { file: PathBuf },
+    /// Write the cards as TOML.
+    Export {
+        #[arg(long)]
+        tag: Option<String>,
+        /// Prints to stdout when omitted.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// List the cards with similar questions.
+    Duplicates,
+}
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let db = Database::load_or_init("db/flashcards.db")?;
-    let media_dir = env::current_dir()?.join(DB_DIR).join("media");
-
-    for card in db.cards_to_review()? {
-        println!("Q: {}", card.question);
-        if let Some(img) = card.img {
-            println!("   file://{}", media_dir.join(img).to_str().unwrap());
-        }
-        println!("   #{}", card.tags.join(", "));
-        println!("Press enter to reveal the answer");
-        readln();
-
-        println!("A: {}", card.answer);
-        println!("OK? (y/n): ");
-        let inpt = readln();
-        match inpt.as_str() {
-            "y" => {
-                db.ok(card.id)?;
+    let cli = Cli::parse();
+    Settings::load()?;
+    let db = Database::get_instance()?.lock().unwrap();
+
+    match cli.command.unwrap_or(Command::Review { tag: None }) {
+        Command::Review { tag } => review(&db, tag),
+        Command::Add {
+            question,
+            answer,
+            examples,
+            source,
+            tags,
+        } => {
+            let template = CardTemplate {
+                question: question.clone().unwrap_or_default(),
+                answer: answer.clone().unwrap_or_default(),
+                examples: examples.unwrap_or_default(),
+                source: source.unwrap_or_default(),
+                tags,
+            };
+            let template = match (question, answer) {
+                (Some(_), Some(_)) => template,
+                _ => edit_in_editor(&template)?,
+            };
+            let mut card = Flashcard::new(String::new(), String::new());
+            template.apply_to(&mut card)?;
+            let id = db.add_card(&card)?;
+            println!("Added card #{}", id);
+            Ok(())
+        }
+        Command::Edit { id } => {
+            let mut card = db.get_card(id)?;
+            edit_in_editor(&CardTemplate::from(&card))?.apply_to(&mut card)?;
+            db.update_card(&card)?;
+            println!("Updated card #{}", id);
+            Ok(())
+        }
+        Command::List { tag } => {
+            for card in db.all_cards(tag)? {
+                let question = card.question.lines().next().unwrap_or_default();
+                println!("#{:<5} {}  [{}]", card.id, question, card.tags.join(", "));
             }
-            "n" => {
-                db.fail(card.id)?;
+            Ok(())
+        }
+        Command::Show { id } => show(&db.get_card(id)?),
+        Command::Delete { id, yes } => {
+            let card = db.get_card(id)?;
+            if yes || confirm(&format!("Delete #{} {}?", card.id, card.question))? {
+                db.delete_card(id)?;
+                println!("Deleted card #{}", id);
             }
-            _ => {
-                println!("Invalid input");
+            Ok(())
+        }
+        Command::Tags => {
+            for (tag, count) in db.tags()? {
+                println!("{:<20} {}", tag, count);
+            }
+            Ok(())
+        }
+        Command::Stats => print_stats(&db),
+        Command::Import { file } => import(&db, &file),
+        Command::Export { tag, output } => {
+            let cards = CardsFile {
+                cards: db.all_cards(tag)?,
+            };
+            let toml = toml::to_string_pretty(&cards)?;
+            match output {
+                Some(output) => fs::write(output, toml)?,
+                None => print!("{}", toml),
             }
+            Ok(())
+        }
+        Command::Duplicates => print_duplicates(&db),
+    }
+}
+
+fn review(db: &Database, tag: Option<String>) -> Result<(), Box<dyn Error>> {
+    let media_dir = Path::new(&Settings::get().db_path).join("media");
+    let cards = db
+        .cards_to_review()?
+        .into_iter()
+        .filter(|card| tag.as_ref().is_none_or(|tag| card.tags.contains(tag)))
+        .collect();
+    let summary = tui::review(db, cards, &media_dir)?;
+    println!("{}", summary);
+    Ok(())
+}
+
+/// The editable card fields, as shown in $EDITOR.
+#[derive(Serialize, Deserialize)]
+struct CardTemplate {
+    question: String,
+    answer: String,
+    #[serde(default)]
+    examples: String,
+    #[serde(default)]
+    source: String,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl CardTemplate {
+    fn apply_to(self, card: &mut Flashcard) -> Result<(), Box<dyn Error>> {
+        let tags: Vec<String> = self
+            .tags
+            .iter()
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        if self.question.trim().is_empty() || self.answer.trim().is_empty() {
+            return Err("The question and the answer can't be empty".into());
+        }
+        // Cards without tags are not listed anywhere.
+        if tags.is_empty() {
+            return Err("At least one tag is required".into());
+        }
+
+        card.question = self.question;
+        card.answer = self.answer;
+        card.examples = Some(self.examples).filter(|examples| !examples.is_empty());
+        card.source = Some(self.source).filter(|source| !source.is_empty());
+        card.tags = tags;
+        Ok(())
+    }
+}
+
+impl From<&Flashcard> for CardTemplate {
+    fn from(card: &Flashcard) -> Self {
+        Self {
+            question: card.question.clone(),
+            answer: card.answer.clone(),
+            examples: card.examples.clone().unwrap_or_default(),
+            source: card.source.clone().unwrap_or_default(),
+            tags: card.tags.clone(),
         }
     }
+}
+
+/// Opens the template in $VISUAL or $EDITOR and parses it back once the editor exits.
+fn edit_in_editor(template: &CardTemplate) -> Result<CardTemplate, Box<dyn Error>> {
+    let path = env::temp_dir().join(format!("flashcard-{}.toml", process::id()));
+    fs::write(&path, toml::to_string_pretty(template)?)?;
 
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut args = editor.split_whitespace();
+    let program = args.next().ok_or("$EDITOR is empty")?;
+    let status = process::Command::new(program)
+        .args(args)
+        .arg(&path)
+        .status()?;
+
+    let contents = fs::read_to_string(&path);
+    fs::remove_file(&path)?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", editor, status).into());
+    }
+    Ok(toml::from_str(&contents?)?)
+}
+
+fn show(card: &Flashcard) -> Result<(), Box<dyn Error>> {
+    println!("#{} [{}]", card.id, card.tags.join(", "));
+    println!();
+    println!("{}", card.question);
+    println!("---");
+    println!("{}", card.answer);
+    if let Some(examples) = &card.examples {
+        println!("---");
+        println!("{}", examples);
+    }
+    println!();
+    if let Some(source) = &card.source {
+        println!("Source: {}", source);
+    }
+    println!(
+        "Last reviewed: {}, next review in {}h",
+        card.last_reviewed.format("%Y-%m-%d %H:%M"),
+        card.review_after_secs / 3600
+    );
     Ok(())
 }
 
-fn readln() -> String {
-    let mut buffer = String::new();
-    io::stdin().read_line(&mut buffer).unwrap();
-    buffer.trim().to_string()
+fn confirm(question: &str) -> Result<bool, Box<dyn Error>> {
+    print!("{} [y/N] ", question);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("y"))
+}
+
+fn print_stats(db: &Database) -> Result<(), Box<dyn Error>> {
+    let cards = db.all_cards(None)?;
+    let due = db.cards_to_review()?;
+    let history = db.review_history()?;
+    let remembered = history.iter().filter(|review| review.remembered).count();
+
+    println!("Cards: {}", cards.len());
+    println!("Due for review: {}", due.len());
+    println!("Tags: {}", db.tags()?.len());
+    println!("Reviews: {}", history.len());
+    if let Some(percent) = (remembered * 100).checked_div(history.len()) {
+        println!("Remembered: {}%", percent);
+        println!(
+            "Reviews per card per month: {:.1}",
+            db.avg_reviews_per_month()?
+        );
+    }
+    Ok(())
+}
+
+/// The format of `export` and `import`.
+#[derive(Serialize, Deserialize)]
+struct CardsFile {
+    cards: Vec<Flashcard>,
+}
+
+/// Adds the cards keeping their review schedule. Cards that already exist are skipped,
+/// so importing the same file twice is harmless.
+fn import(db: &Database, file: &Path) -> Result<(), Box<dyn Error>> {
+    let CardsFile { cards } = toml::from_str(&fs::read_to_string(file)?)?;
+    let existing = db.all_cards(None)?;
+
+    let (mut added, mut skipped) = (0, 0);
+    for card in cards {
+        let exists = existing
+            .iter()
+            .any(|other| other.question == card.question && other.answer == card.answer);
+        if exists {
+            skipped += 1;
+        } else {
+            db.add_card(&card)?;
+            added += 1;
+        }
+    }
+    println!(
+        "Imported {} cards, skipped {} existing ones",
+        added, skipped
+    );
+    Ok(())
+}
+
+/// Lists the cards with similar questions, the most similar first.
+fn print_duplicates(db: &Database) -> Result<(), Box<dyn Error>> {
+    let cards = db.all_cards(None)?;
+    for pair in find_duplicates(&cards, SIMILARITY_THRESHOLD) {
+        println!("{:.0}% similar:", pair.similarity * 100.0);
+        println!("   #{} {}", pair.card.id, pair.card.question);
+        println!("   #{} {}", pair.duplicate.id, pair.duplicate.question);
+    }
+    Ok(())
 }
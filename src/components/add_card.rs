@@ -3,11 +3,17 @@ use leptos::prelude::*;
 use leptos::*;
 use leptos_router::{hooks::use_query, params::Params};
 
+use crate::components::markdown_editor::MarkdownEditor;
+use crate::components::rich_text_warnings;
 #[cfg(feature = "ssr")]
 use crate::db::Database;
+#[cfg(feature = "ssr")]
+use crate::duplicates::{find_similar, SIMILARITY_THRESHOLD};
 use crate::model::Flashcard;
 use leptos::wasm_bindgen::JsCast;
 
+/// Adds the card, unless there are existing cards with similar questions: then they are returned
+/// and nothing is added. `force` adds the card anyway.
 #[server(SubmitCard, "/api")]
 pub async fn submit_card(
     question: String,
@@ -17,9 +23,20 @@ pub async fn submit_card(
     tags: String,
     answer_img_fname: Option<String>,
     question_img_fname: Option<String>,
-) -> Result<(), ServerFnError> {
+    force: bool,
+) -> Result<Vec<Flashcard>, ServerFnError> {
     let db = Database::get_instance().unwrap().lock().unwrap();
 
+    if !force {
+        let existing_cards = db
+            .all_cards(None)
+            .map_err(|e| ServerFnError::new(e.to_string()))?;
+        let similar_cards = find_similar(&question, &existing_cards, SIMILARITY_THRESHOLD);
+        if !similar_cards.is_empty() {
+            return Ok(similar_cards);
+        }
+    }
+
     let mut card = Flashcard::new(question, answer);
     card.examples = Some(examples);
     card.source = source;
@@ -28,26 +45,79 @@ pub async fn submit_card(
     card.question_img = question_img_fname;
 
     db.add_card(&card)
-        .map_err(|e| ServerFnError::new(e.to_string()))
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    Ok(Vec::new())
+}
+
+/// The text fields of the card being edited in [FlashcardForm].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CardText {
+    pub question: RwSignal<String>,
+    pub answer: RwSignal<String>,
+    pub examples: RwSignal<String>,
+}
+
+impl CardText {
+    pub fn new(card: &Flashcard) -> Self {
+        let text = Self::default();
+        text.set(card);
+        text
+    }
+
+    pub fn set(&self, card: &Flashcard) {
+        self.question.set(card.question.clone());
+        self.answer.set(card.answer.clone());
+        self.examples.set(card.examples.clone().unwrap_or_default());
+    }
+
+    /// Mistakes in the rich text of all the fields, see [rich_text_warnings].
+    pub fn warnings(&self) -> Vec<String> {
+        [
+            ("Question", self.question),
+            ("Answer", self.answer),
+            ("Examples", self.examples),
+        ]
+        .into_iter()
+        .flat_map(|(field, text)| {
+            rich_text_warnings(&text.get_untracked())
+                .into_iter()
+                .map(move |warning| format!("{}: {}", field, warning))
+        })
+        .collect()
+    }
+}
+
+/// Cancels submitting a card with rich text warnings, unless it's confirmed.
+/// Must run before [ActionForm] handles the submit, e.g. with `on:submit:capture`.
+pub fn confirm_warnings(ev: &ev::SubmitEvent, text: &CardText) {
+    let warnings = text.warnings();
+    if warnings.is_empty() {
+        return;
+    }
+    let message = format!(
+        "The card may not render as intended:\n{}\n\nSave it anyway?",
+        warnings.join("\n")
+    );
+    if !window().confirm_with_message(&message).unwrap_or(false) {
+        ev.prevent_default();
+    }
 }
 
-/// Reused to add or edit a card.
+/// Reused to add or edit a card. Pass `text` to read or change the text while it's edited.
 #[component]
-pub fn FlashcardForm(#[prop(into)] card: Flashcard) -> impl IntoView {
+pub fn FlashcardForm(
+    #[prop(into)] card: Flashcard,
+    #[prop(optional)] text: Option<CardText>,
+) -> impl IntoView {
+    let text = text.unwrap_or_else(|| CardText::new(&card));
     let answer_img_fname = NodeRef::<html::Input>::new();
     let question_img_fname = NodeRef::<html::Input>::new();
 
     view! {
         <div>
-            <label class="flex flex-col gap-2">
-                <span>Question*:</span>
-                <input
-                    class="border rounded px-3 py-2"
-                    type="text"
-                    name="question"
-                    required=true
-                    value=card.question
-                />
+            <div class="flex flex-col gap-2">
+                <label for="question">Question*:</label>
+                <MarkdownEditor name="question" text=text.question rows=2 required=true />
                 <label class="flex flex-col gap-2 ml-4">
                     <span>Image:</span>
                     <input
@@ -68,12 +138,10 @@ pub fn FlashcardForm(#[prop(into)] card: Flashcard) -> impl IntoView {
                     />
                     <input type="hidden" name="question_img_fname" node_ref=question_img_fname />
                 </label>
-            </label>
-            <label class="flex flex-col gap-2">
-                <span>Answer:</span>
-                <textarea class="border rounded px-3 py-2" name="answer" rows=4 cols=80>
-                    {card.answer}
-                </textarea>
+            </div>
+            <div class="flex flex-col gap-2">
+                <label for="answer">Answer:</label>
+                <MarkdownEditor name="answer" text=text.answer />
                 <label class="flex flex-col gap-2 ml-4">
                     <span>Image:</span>
                     <input
@@ -94,13 +162,11 @@ pub fn FlashcardForm(#[prop(into)] card: Flashcard) -> impl IntoView {
                     />
                     <input type="hidden" name="answer_img_fname" node_ref=answer_img_fname />
                 </label>
-            </label>
-            <label class="flex flex-col gap-2">
-                <span>Examples:</span>
-                <textarea class="border rounded px-3 py-2" name="examples" rows=4 cols=80>
-                    {card.examples}
-                </textarea>
-            </label>
+            </div>
+            <div class="flex flex-col gap-2">
+                <label for="examples">Examples:</label>
+                <MarkdownEditor name="examples" text=text.examples />
+            </div>
             <label class="flex flex-col gap-2">
                 <span>Source:</span>
                 <input
@@ -152,32 +218,56 @@ pub fn AddCard() -> impl IntoView {
         card.tags = vec![tag];
     }
 
+    let text = CardText::new(&card);
     let submit = ServerAction::<SubmitCard>::new();
     let form_ref = NodeRef::<leptos::html::Form>::new();
     let show_ack = RwSignal::new(false);
+    let similar_cards = RwSignal::new(Vec::<Flashcard>::new());
+    // Set once the duplicates were shown, to add the card anyway on the next submit.
+    let force = RwSignal::new(false);
+    // Bumped to rerender the form, so that the editor previews are reset too.
+    let form_version = RwSignal::new(0);
 
     // Watch for successful form submission.
+    let new_card = card.clone();
     Effect::new(move |_| {
-        if let Some(Ok(_)) = submit.value().get() {
+        if let Some(Ok(similar)) = submit.value().get() {
+            if !similar.is_empty() {
+                similar_cards.set(similar);
+                force.set(true);
+                return;
+            }
             show_ack.set(true);
+            similar_cards.set(Vec::new());
+            force.set(false);
             if let Some(form) = form_ref.get() {
                 form.reset();
             }
+            text.set(&new_card);
+            form_version.update(|v| *v += 1);
             Timeout::new(3000, move || show_ack.set(false)).forget();
         }
     });
 
     view! {
-        <div class="max-w-[600px] mx-auto my-8 p-4">
-            <div class="flex flex-col gap-4 w-full max-w-md bg-white p-8 rounded shadow">
-                <ActionForm action=submit node_ref=form_ref>
+        <div class="max-w-[1200px] mx-auto my-8 p-4">
+            <div class="flex flex-col gap-4 w-full bg-white p-8 rounded shadow">
+                <ActionForm
+                    action=submit
+                    node_ref=form_ref
+                    on:submit:capture=move |ev| confirm_warnings(&ev, &text)
+                >
                     <h2 class="text-2xl font-bold mb-4">{"Add a new card"}</h2>
-                    <FlashcardForm card=card />
+                    {move || {
+                        form_version.track();
+                        view! { <FlashcardForm card=card.clone() text=text /> }
+                    }}
+                    <input type="hidden" name="force" prop:value=move || force.get().to_string() />
                     <button
                         class="bg-blue-500 text-white px-6 py-2 rounded hover:bg-blue-600 transition mt-4"
                         type="submit"
                     >
-                        {"Create Flashcard"}
+                        {move || if force.get() { "Create Anyway" } else { "Create Flashcard" }}
                     </button>
                 </ActionForm>
                 <Show when=move || show_ack.get()>
@@ -185,6 +275,29 @@ pub fn AddCard() -> impl IntoView {
                         {"Card added successfully!"}
                     </div>
                 </Show>
+                <Show when=move || !similar_cards.get().is_empty()>
+                    <div class="text-amber-600 mt-2">
+                        {"The card was not added, it may be a duplicate of:"}
+                        <ul class="list-disc ml-6">
+                            <For
+                                each=move || similar_cards.get()
+                                key=|card| card.id
+                                children=move |card| {
+                                    view! {
+                                        <li>
+                                            <a class="underline" href=format!("/cards/{}", card.id)>
+                                                {card.question}
+                                            </a>
+                                        </li>
+                                    }
+                                }
+                            />
+                        </ul>
+                        <a class="underline" href="/duplicates">
+                            {"Review all duplicates"}
+                        </a>
+                    </div>
+                </Show>
             </div>
         </div>
     }
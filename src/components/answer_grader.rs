@@ -0,0 +1,123 @@
+use leptos::prelude::*;
+use thaw::Spinner;
+
+#[cfg(feature = "ssr")]
+use crate::assistant::Assistant;
+use crate::components::ShowError;
+#[cfg(feature = "ssr")]
+use crate::db::Database;
+use crate::errors::AppError;
+#[cfg(feature = "ssr")]
+use crate::languages::progress::record_card_review;
+use crate::model::{AnswerGrade, Grade};
+
+/// Grades the typed answer with an LLM and records the review with the given grade.
+#[server(GradeAnswer, "/api")]
+async fn grade_answer(card_id: i64, answer: String) -> Result<AnswerGrade, AppError> {
+    // Don't hold the database lock while waiting for the LLM.
+    let card = {
+        let db = Database::get_instance().unwrap().lock().unwrap();
+        db.get_card(card_id)
+            .map_err(|e| AppError::DuckdbError(e.to_string()))?
+    };
+
+    let grade = Assistant::from_settings()
+        .grade_answer(&card, &answer)
+        .await?;
+
+    {
+        let db = Database::get_instance().unwrap().lock().unwrap();
+        db.review_with_feedback(card_id, grade.grade, Some(&grade.explanation))
+            .map_err(|e| AppError::DuckdbError(e.to_string()))?;
+    }
+    record_card_review(&card, grade.grade.remembered());
+    Ok(grade)
+}
+
+/// Instead of self-grading, the answer is typed and graded by an LLM.
+#[component]
+pub fn AnswerGrader(
+    card_id: i64,
+    /// Called once the answer is graded, so that the correct answer can be shown.
+    #[prop(into)]
+    on_graded: Callback<()>,
+    /// Move on to the next card.
+    #[prop(into)]
+    on_next: Callback<()>,
+) -> impl IntoView {
+    let show_error = ShowError::from_ctx();
+    let answer = RwSignal::new(String::new());
+
+    let grade = Action::new(move |answer: &String| {
+        let answer = answer.clone();
+        async move {
+            match grade_answer(card_id, answer).await {
+                Ok(grade) => {
+                    on_graded.run(());
+                    Some(grade)
+                }
+                Err(e) => {
+                    show_error.show(format!("Failed to grade the answer:\n {}", e));
+                    None
+                }
+            }
+        }
+    });
+    let grading = grade.pending();
+    let result = move || grade.value().get().flatten();
+
+    view! {
+        <div class="flex flex-col gap-4 w-full">
+            <Show
+                when=move || result().is_none()
+                fallback=move || {
+                    let AnswerGrade { grade, explanation } = result().unwrap();
+                    view! {
+                        <div class="bg-white border border-slate-200 rounded-lg p-4 shadow">
+                            <span class=format!(
+                                "inline-block rounded-full px-3 py-1 text-sm font-semibold {}",
+                                grade_class(grade),
+                            )>{grade.as_str()}</span>
+                            <p class="mt-2 text-gray-700">{explanation}</p>
+                        </div>
+                        <button
+                            class="self-center bg-blue-100 text-blue-700 border-none rounded-md px-6 py-3 text-base cursor-pointer transition-colors hover:bg-blue-200"
+                            on:click=move |_| on_next.run(())
+                        >
+                            "Next"
+                        </button>
+                    }
+                }
+            >
+                <textarea
+                    class="border rounded px-3 py-2"
+                    rows=3
+                    placeholder="Type your answer"
+                    prop:value=move || answer.get()
+                    on:input=move |ev| answer.set(event_target_value(&ev))
+                ></textarea>
+                <button
+                    class="self-center bg-blue-100 text-blue-700 border-none rounded-md px-6 py-3 text-base cursor-pointer transition-colors hover:bg-blue-200 disabled:opacity-50"
+                    disabled=move || grading.get() || answer.get().trim().is_empty()
+                    on:click=move |_| {
+                        grade.dispatch(answer.get());
+                    }
+                >
+                    "Check answer"
+                </button>
+                <Show when=move || grading.get()>
+                    <Spinner />
+                </Show>
+            </Show>
+        </div>
+    }
+}
+
+fn grade_class(grade: Grade) -> &'static str {
+    match grade {
+        Grade::Again => "bg-red-100 text-red-600",
+        Grade::Hard => "bg-amber-100 text-amber-700",
+        Grade::Good => "bg-blue-100 text-blue-700",
+        Grade::Easy => "bg-green-100 text-green-700",
+    }
+}
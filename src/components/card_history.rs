@@ -0,0 +1,126 @@
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use similar::{ChangeTag, TextDiff};
+
+use crate::components::error_notification::ErrorNotification;
+#[cfg(feature = "ssr")]
+use crate::db::Database;
+use crate::model::{CardRevision, Flashcard};
+
+#[server(GetCardRevisions, "/api")]
+async fn get_card_revisions(card_id: i64) -> Result<Vec<CardRevision>, ServerFnError> {
+    let db = Database::get_instance().unwrap().lock().unwrap();
+    db.card_revisions(card_id)
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(RestoreCardRevision, "/api")]
+async fn restore_card_revision(revision_id: i64) -> Result<(), ServerFnError> {
+    let db = Database::get_instance().unwrap().lock().unwrap();
+    db.restore_revision(revision_id)
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// Lists the previous versions of a card, each diffed against the version that replaced it.
+#[component]
+pub fn CardHistory(
+    #[prop(into)] card: Flashcard,
+    /// Called after a previous version was restored.
+    #[prop(into)]
+    on_restore: Callback<()>,
+) -> impl IntoView {
+    let card_id = card.id;
+    let (revisions, set_revisions) = signal(Vec::<CardRevision>::new());
+    let (error, set_error) = signal(None::<String>);
+
+    Effect::new(move |_| {
+        spawn_local(async move {
+            match get_card_revisions(card_id).await {
+                Ok(loaded) => set_revisions.set(loaded),
+                Err(e) => set_error.set(Some(format!("Failed to load card history:\n {}", e))),
+            }
+        });
+    });
+
+    let restore = move |revision_id: i64| {
+        spawn_local(async move {
+            match restore_card_revision(revision_id).await {
+                Ok(_) => on_restore.run(()),
+                Err(e) => set_error.set(Some(format!("Failed to restore the card:\n {}", e))),
+            }
+        });
+    };
+
+    view! {
+        <div class="max-w-[600px] mx-auto my-8 p-4 flex flex-col gap-4">
+            <Show when=move || revisions.get().is_empty()>
+                <div class="text-center">"This card was never edited."</div>
+            </Show>
+            {move || {
+                let revisions = revisions.get();
+                revisions
+                    .iter()
+                    .enumerate()
+                    .map(|(i, revision)| {
+                        let (newer_question, newer_answer, newer_examples) = if i == 0 {
+                            (card.question.clone(), card.answer.clone(), card.examples.clone())
+                        } else {
+                            let newer = &revisions[i - 1];
+                            (newer.question.clone(), newer.answer.clone(), newer.examples.clone())
+                        };
+                        let revision_id = revision.id;
+                        view! {
+                            <div class="bg-white border border-slate-200 rounded-lg p-6 shadow">
+                                <div class="flex justify-between items-center mb-4">
+                                    <span class="text-slate-400 text-xs">
+                                        "Edited on "
+                                        {revision.revised_at.format("%Y-%m-%d %H:%M").to_string()}
+                                    </span>
+                                    <button
+                                        class="bg-blue-100 text-blue-700 rounded-md px-3 py-1 text-sm hover:bg-blue-200"
+                                        on:click=move |_| restore(revision_id)
+                                    >
+                                        "Restore this version"
+                                    </button>
+                                </div>
+                                <b>"Question:"</b>
+                                <DiffText old=revision.question.clone() new=newer_question />
+                                <b>"Answer:"</b>
+                                <DiffText old=revision.answer.clone() new=newer_answer />
+                                <b>"Examples:"</b>
+                                <DiffText
+                                    old=revision.examples.clone().unwrap_or_default()
+                                    new=newer_examples.unwrap_or_default()
+                                />
+                            </div>
+                        }
+                    })
+                    .collect_view()
+            }}
+            <ErrorNotification error=error />
+        </div>
+    }
+}
+
+/// Word level diff: removed words are struck through, added ones are highlighted.
+#[component]
+pub fn DiffText(#[prop(into)] old: String, #[prop(into)] new: String) -> impl IntoView {
+    let changes = TextDiff::from_words(&old, &new)
+        .iter_all_changes()
+        .map(|change| {
+            let text = change.value().to_string();
+            match change.tag() {
+                ChangeTag::Equal => view! { <span>{text}</span> }.into_any(),
+                ChangeTag::Delete => {
+                    view! { <del class="bg-red-100 text-red-700">{text}</del> }.into_any()
+                }
+                ChangeTag::Insert => {
+                    view! { <ins class="bg-green-100 text-green-700 no-underline">{text}</ins> }
+                        .into_any()
+                }
+            }
+        })
+        .collect_view();
+
+    view! { <p class="whitespace-pre-wrap mb-2">{changes}</p> }
+}
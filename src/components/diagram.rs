@@ -0,0 +1,60 @@
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "ssr")]
+use crate::components::richtext::escape_html;
+#[cfg(feature = "ssr")]
+use crate::diagram::render_svg;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DiagramKind {
+    Dot,
+    Mermaid,
+}
+
+impl DiagramKind {
+    /// Maps the info string of a fenced code block to a diagram kind.
+    pub fn from_info_string(info: &str) -> Option<Self> {
+        match info {
+            "dot" | "graphviz" => Some(DiagramKind::Dot),
+            "mermaid" => Some(DiagramKind::Mermaid),
+            _ => None,
+        }
+    }
+}
+
+/// Render a diagram as inline SVG.
+/// Diagrams that fail to render are shown as code.
+/// The layout runs on the server, see [crate::diagram].
+#[component]
+pub fn Diagram(kind: DiagramKind, source: String) -> impl IntoView {
+    #[cfg(feature = "ssr")]
+    let html = diagram_html(kind, &source);
+    // Hydration keeps the SVG from the server until the client gets it too.
+    #[cfg(not(feature = "ssr"))]
+    let html = {
+        let html = LocalResource::new(move || render_diagram(kind, source.clone()));
+        move || html.get().and_then(Result::ok).unwrap_or_default()
+    };
+    view! { <div class="overflow-x-auto" inner_html=html></div> }
+}
+
+#[server(RenderDiagram, "/api")]
+async fn render_diagram(kind: DiagramKind, source: String) -> Result<String, ServerFnError> {
+    Ok(diagram_html(kind, &source))
+}
+
+#[cfg(feature = "ssr")]
+fn diagram_html(kind: DiagramKind, source: &str) -> String {
+    match render_svg(kind, source) {
+        Ok(svg) => svg,
+        Err(e) => {
+            log::warn!("Failed to render the diagram: {}", e);
+            format!(
+                r#"<pre title="{}"><code>{}</code></pre>"#,
+                escape_html(&e),
+                escape_html(source)
+            )
+        }
+    }
+}
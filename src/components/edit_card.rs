@@ -1,4 +1,5 @@
-use crate::components::add_card::FlashcardForm;
+use crate::components::add_card::{confirm_warnings, CardText, FlashcardForm};
+use crate::components::improve_card::{CardField, ImproveCard};
 #[cfg(feature = "ssr")]
 use crate::db::Database;
 use crate::model::Flashcard;
@@ -54,6 +55,8 @@ struct EditCardParams {
 #[component]
 pub fn EditCard() -> impl IntoView {
     let (card, set_card) = signal(None::<Flashcard>);
+    // What is in the form, with the unsaved edits.
+    let text = CardText::default();
     let submit = ServerAction::<UpdateCard>::new();
     let form_ref = NodeRef::<leptos::html::Form>::new();
     let show_ack = RwSignal::new(false);
@@ -72,6 +75,7 @@ pub fn EditCard() -> impl IntoView {
     Effect::new(move |_| {
         spawn_local(async move {
             if let Ok(fetched_card) = get_card(id()).await {
+                text.set(&fetched_card);
                 set_card.set(Some(fetched_card));
             } else {
                 web_sys::console::error_1(&"Failed to fetch card".into());
@@ -88,8 +92,8 @@ pub fn EditCard() -> impl IntoView {
     });
 
     view! {
-        <div class="max-w-[600px] mx-auto my-8 p-4">
-            <div class="flex flex-col gap-4 w-full max-w-md bg-white p-8 rounded shadow">
+        <div class="max-w-[1200px] mx-auto my-8 p-4">
+            <div class="flex flex-col gap-4 w-full bg-white p-8 rounded shadow">
                 <Show
                     when=move || card.get().is_some()
                     fallback=|| view! { <div>"Loading..."</div> }
@@ -97,10 +101,14 @@ pub fn EditCard() -> impl IntoView {
                     {move || {
                         let card = card.get().unwrap();
                         view! {
-                            <ActionForm action=submit node_ref=form_ref>
+                            <ActionForm
+                                action=submit
+                                node_ref=form_ref
+                                on:submit:capture=move |ev| confirm_warnings(&ev, &text)
+                            >
                                 <h2 class="text-2xl font-bold mb-4">{"Edit card"}</h2>
                                 <input type="hidden" name="id" value=card.id />
-                                <FlashcardForm card=card />
+                                <FlashcardForm card=card text=text />
                                 <button
                                     class="bg-blue-500 text-white px-6 py-2 rounded hover:bg-blue-600 transition mt-4"
                                     type="submit"
@@ -115,6 +123,19 @@ pub fn EditCard() -> impl IntoView {
                             </Show>
                         }
                     }}
+                    // Suggestions are for the text in the form, accepting one only changes its field.
+                    <ImproveCard
+                        card=Signal::derive(move || {
+                            let mut card = card.get().unwrap();
+                            card.question = text.question.get();
+                            card.answer = text.answer.get();
+                            card.examples = Some(text.examples.get());
+                            card
+                        })
+                        on_accept=move |field: CardField, value: String| {
+                            field.text(&text).set(value)
+                        }
+                    />
                 </Show>
             </div>
         </div>
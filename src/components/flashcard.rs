@@ -1,3 +1,7 @@
+use crate::components::answer_grader::AnswerGrader;
+use crate::components::multiple_choice::MultipleChoice;
+use crate::components::review_cards::ReviewMode;
+use crate::components::typed_answer::TypedAnswer;
 use crate::components::RichText;
 use crate::model;
 use crate::model::FlashcardAnswer;
@@ -7,6 +11,12 @@ use leptos::prelude::*;
 pub fn Flashcard(
     #[prop(into)] card: model::Flashcard,
     #[prop(into)] on_answer: Callback<FlashcardAnswer>,
+    /// Other modes than self-grading need `on_next`.
+    #[prop(default = ReviewMode::SelfGraded)]
+    mode: ReviewMode,
+    /// Called to move on to the next card once the answer is graded or picked.
+    #[prop(default = None)]
+    on_next: Option<Callback<()>>,
 ) -> impl IntoView {
     let (show_answer, set_show_answer) = signal(false);
     let (show_examples, set_show_examples) = signal(false);
@@ -128,36 +138,75 @@ pub fn Flashcard(
                 </a>
             </div>
             <div class="mt-4 flex justify-center">
-                <Show
-                    when=move || !show_answer.get()
-                    fallback=move || {
+                {match (mode, on_next) {
+                    (ReviewMode::AiGraded, Some(on_next)) => {
                         view! {
-                            <div class="flex gap-4">
-                                <button
-                                    class="bg-blue-100 text-blue-700 border-none rounded-md px-6 py-3 text-base cursor-pointer transition-colors hover:bg-blue-200"
-                                    on:click=move |_| handle_answer(FlashcardAnswer::Remember)
-                                >
-                                    "Remember"
-                                </button>
-                                <button
-                                    class="bg-red-100 text-red-600 border-none rounded-md px-6 py-3 text-base cursor-pointer transition-colors hover:bg-red-200"
-                                    on:click=move |_| handle_answer(FlashcardAnswer::Not)
-                                >
-                                    "Not"
-                                </button>
-                            </div>
+                            <AnswerGrader
+                                card_id=card.id
+                                on_graded=move || set_show_answer.set(true)
+                                on_next=on_next
+                            />
                         }
+                            .into_any()
                     }
-                >
-                    <div class="flex gap-4">
-                        <button
-                            on:click=move |_| set_show_answer.update(|value| *value = !*value)
-                            class="bg-blue-100 text-blue-700 border-none rounded-md px-6 py-3 text-base cursor-pointer transition-colors hover:bg-blue-200"
-                        >
-                            "Show Answer"
-                        </button>
-                    </div>
-                </Show>
+                    (ReviewMode::MultipleChoice, Some(on_next)) => {
+                        view! {
+                            <MultipleChoice
+                                card_id=card.id
+                                on_answered=move || set_show_answer.set(true)
+                                on_next=on_next
+                            />
+                        }
+                            .into_any()
+                    }
+                    (ReviewMode::Typed, Some(on_next)) => {
+                        view! {
+                            <TypedAnswer
+                                card_id=card.id
+                                on_checked=move || set_show_answer.set(true)
+                                on_next=on_next
+                            />
+                        }
+                            .into_any()
+                    }
+                    _ => {
+                        view! {
+                            <Show
+                                when=move || !show_answer.get()
+                                fallback=move || {
+                                    view! {
+                                        <div class="flex gap-4">
+                                            <button
+                                                class="bg-blue-100 text-blue-700 border-none rounded-md px-6 py-3 text-base cursor-pointer transition-colors hover:bg-blue-200"
+                                                on:click=move |_| handle_answer(FlashcardAnswer::Remember)
+                                            >
+                                                "Remember"
+                                            </button>
+                                            <button
+                                                class="bg-red-100 text-red-600 border-none rounded-md px-6 py-3 text-base cursor-pointer transition-colors hover:bg-red-200"
+                                                on:click=move |_| handle_answer(FlashcardAnswer::Not)
+                                            >
+                                                "Not"
+                                            </button>
+                                        </div>
+                                    }
+                                }
+                            >
+                                <div class="flex gap-4">
+                                    <button
+                                        on:click=move |_| {
+                                            set_show_answer.update(|value| *value = !*value)
+                                        }
+                                        class="bg-blue-100 text-blue-700 border-none rounded-md px-6 py-3 text-base cursor-pointer transition-colors hover:bg-blue-200"
+                                    >
+                                        "Show Answer"
+                                    </button>
+                                </div>
+                            </Show>
+                        }
+                            .into_any()
+                    }
+                }}
             </div>
         </div>
     }
@@ -0,0 +1,235 @@
+use leptos::prelude::*;
+use server_fn::codec::Json;
+use thaw::Spinner;
+
+#[cfg(feature = "ssr")]
+use crate::assistant::Assistant;
+use crate::components::ShowError;
+#[cfg(feature = "ssr")]
+use crate::db::Database;
+use crate::errors::AppError;
+use crate::model::Flashcard;
+
+#[server(GenerateCards, "/api")]
+async fn generate_cards(notes: String) -> Result<Vec<Flashcard>, AppError> {
+    Assistant::from_settings().generate_cards(&notes).await
+}
+
+// JSON, because the default URL encoding doesn't handle a list of structs well.
+#[server(name = SaveCards, prefix = "/api", input = Json)]
+async fn save_cards(
+    cards: Vec<Flashcard>,
+    tags: String,
+    source: String,
+) -> Result<usize, ServerFnError> {
+    let tags: Vec<String> = tags
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if tags.is_empty() {
+        return Err(ServerFnError::new("At least one tag is required"));
+    }
+
+    let db = Database::get_instance().unwrap().lock().unwrap();
+    let count = cards.len();
+    for mut card in cards.into_iter() {
+        card.tags = tags.clone();
+        card.source = Some(source.clone()).filter(|s| !s.is_empty());
+        db.add_card(&card)
+            .map_err(|e| ServerFnError::new(e.to_string()))?;
+    }
+    Ok(count)
+}
+
+/// A generated card that can be edited before saving.
+#[derive(Clone, Copy)]
+struct Draft {
+    id: usize,
+    question: RwSignal<String>,
+    answer: RwSignal<String>,
+    accepted: RwSignal<bool>,
+}
+
+impl Draft {
+    fn to_flashcard(self) -> Flashcard {
+        Flashcard::new(self.question.get_untracked(), self.answer.get_untracked())
+    }
+}
+
+/// Drafts flashcards from pasted notes with an LLM.
+#[component]
+pub fn GenerateCards() -> impl IntoView {
+    let show_error = ShowError::from_ctx();
+    let notes = RwSignal::new(String::new());
+    let drafts = RwSignal::new(Vec::<Draft>::new());
+    let tags = RwSignal::new(String::new());
+    let source = RwSignal::new(String::new());
+    let saved = RwSignal::new(None::<usize>);
+    // Ids are never reused, so that regenerated drafts are rendered anew.
+    let next_draft_id = StoredValue::new(0usize);
+
+    let generate = Action::new(move |notes: &String| {
+        let notes = notes.clone();
+        async move {
+            match generate_cards(notes).await {
+                Ok(cards) => {
+                    saved.set(None);
+                    drafts.set(
+                        cards
+                            .into_iter()
+                            .map(|card| Draft {
+                                id: {
+                                    let id = next_draft_id.get_value();
+                                    next_draft_id.set_value(id + 1);
+                                    id
+                                },
+                                question: RwSignal::new(card.question),
+                                answer: RwSignal::new(card.answer),
+                                accepted: RwSignal::new(true),
+                            })
+                            .collect(),
+                    );
+                }
+                Err(e) => show_error.show(format!("Failed to generate cards:\n {}", e)),
+            }
+        }
+    });
+    let generating = generate.pending();
+
+    let save = Action::new(move |_: &()| {
+        let cards: Vec<Flashcard> = drafts
+            .get_untracked()
+            .into_iter()
+            .filter(|draft| draft.accepted.get_untracked())
+            .map(Draft::to_flashcard)
+            .collect();
+        async move {
+            match save_cards(cards, tags.get_untracked(), source.get_untracked()).await {
+                Ok(count) => {
+                    drafts.set(Vec::new());
+                    saved.set(Some(count));
+                }
+                Err(e) => show_error.show(format!("Failed to save cards:\n {}", e)),
+            }
+        }
+    });
+    let accepted_count = move || {
+        drafts
+            .get()
+            .iter()
+            .filter(|draft| draft.accepted.get())
+            .count()
+    };
+
+    view! {
+        <div class="max-w-[1200px] mx-auto my-8 p-4">
+            <div class="flex flex-col gap-4 w-full bg-white p-8 rounded shadow">
+                <h2 class="text-2xl font-bold">{"Generate cards from notes"}</h2>
+                <textarea
+                    class="border rounded px-3 py-2 font-mono"
+                    rows=12
+                    placeholder="Paste notes or Markdown documentation here"
+                    prop:value=move || notes.get()
+                    on:input=move |ev| notes.set(event_target_value(&ev))
+                ></textarea>
+                <button
+                    class="self-start bg-blue-500 text-white px-6 py-2 rounded hover:bg-blue-600 transition disabled:opacity-50"
+                    disabled=move || generating.get() || notes.get().trim().is_empty()
+                    on:click=move |_| {
+                        generate.dispatch(notes.get());
+                    }
+                >
+                    {"Generate"}
+                </button>
+                <Show when=move || generating.get()>
+                    <Spinner />
+                </Show>
+
+                <For
+                    each=move || drafts.get()
+                    key=|draft| draft.id
+                    children=move |draft| view! { <DraftCard draft=draft /> }
+                />
+
+                <Show when=move || !drafts.get().is_empty()>
+                    <div class="flex flex-col gap-4 border-t pt-4">
+                        <label class="flex flex-col gap-2">
+                            <span>Tags (comma separated)*:</span>
+                            <input
+                                class="border rounded px-3 py-2"
+                                type="text"
+                                prop:value=move || tags.get()
+                                on:input=move |ev| tags.set(event_target_value(&ev))
+                            />
+                        </label>
+                        <label class="flex flex-col gap-2">
+                            <span>Source:</span>
+                            <input
+                                class="border rounded px-3 py-2"
+                                type="text"
+                                placeholder="e.g. a link to the documentation"
+                                prop:value=move || source.get()
+                                on:input=move |ev| source.set(event_target_value(&ev))
+                            />
+                        </label>
+                        <button
+                            class="self-start bg-blue-500 text-white px-6 py-2 rounded hover:bg-blue-600 transition disabled:opacity-50"
+                            disabled=move || {
+                                accepted_count() == 0 || tags.get().trim().is_empty()
+                                    || save.pending().get()
+                            }
+                            on:click=move |_| {
+                                save.dispatch(());
+                            }
+                        >
+                            {move || format!("Save {} cards", accepted_count())}
+                        </button>
+                    </div>
+                </Show>
+                {move || {
+                    saved
+                        .get()
+                        .map(|count| {
+                            view! {
+                                <div class="text-green-600 font-semibold">
+                                    {format!("Saved {} cards!", count)}
+                                </div>
+                            }
+                        })
+                }}
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn DraftCard(draft: Draft) -> impl IntoView {
+    view! {
+        <div
+            class="flex flex-col gap-2 border border-slate-200 rounded-lg p-4"
+            class:opacity-50=move || !draft.accepted.get()
+        >
+            <label class="flex items-center gap-2 text-sm text-slate-600">
+                <input
+                    type="checkbox"
+                    prop:checked=move || draft.accepted.get()
+                    on:change=move |ev| draft.accepted.set(event_target_checked(&ev))
+                />
+                {"Keep this card"}
+            </label>
+            <textarea
+                class="border rounded px-3 py-2 font-semibold"
+                rows=2
+                prop:value=move || draft.question.get()
+                on:input=move |ev| draft.question.set(event_target_value(&ev))
+            ></textarea>
+            <textarea
+                class="border rounded px-3 py-2"
+                rows=3
+                prop:value=move || draft.answer.get()
+                on:input=move |ev| draft.answer.set(event_target_value(&ev))
+            ></textarea>
+        </div>
+    }
+}
@@ -0,0 +1,135 @@
+use leptos::prelude::*;
+use thaw::Spinner;
+
+#[cfg(feature = "ssr")]
+use crate::assistant::Assistant;
+use crate::components::add_card::CardText;
+use crate::components::card_history::DiffText;
+use crate::components::ShowError;
+use crate::errors::AppError;
+use crate::model::{CardSuggestion, Flashcard};
+
+#[server(ImproveCardContent, "/api")]
+async fn improve_card_content(
+    question: String,
+    answer: String,
+    examples: Option<String>,
+) -> Result<CardSuggestion, AppError> {
+    let mut card = Flashcard::new(question, answer);
+    card.examples = examples;
+    Assistant::from_settings().improve_card(&card).await
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CardField {
+    Question,
+    Answer,
+    Examples,
+}
+
+impl CardField {
+    fn label(&self) -> &'static str {
+        match self {
+            CardField::Question => "Question",
+            CardField::Answer => "Answer",
+            CardField::Examples => "Examples",
+        }
+    }
+
+    pub fn get(&self, card: &Flashcard) -> String {
+        match self {
+            CardField::Question => card.question.clone(),
+            CardField::Answer => card.answer.clone(),
+            CardField::Examples => card.examples.clone().unwrap_or_default(),
+        }
+    }
+
+    /// The field in the form.
+    pub fn text(&self, text: &CardText) -> RwSignal<String> {
+        match self {
+            CardField::Question => text.question,
+            CardField::Answer => text.answer,
+            CardField::Examples => text.examples,
+        }
+    }
+}
+
+/// Asks an LLM to improve the card and shows the suggestions as diffs that can be accepted
+/// field by field.
+#[component]
+pub fn ImproveCard(
+    #[prop(into)] card: Signal<Flashcard>,
+    #[prop(into)] on_accept: Callback<(CardField, String)>,
+) -> impl IntoView {
+    let show_error = ShowError::from_ctx();
+
+    let improve = Action::new(move |card: &Flashcard| {
+        let card = card.clone();
+        async move {
+            match improve_card_content(card.question, card.answer, card.examples).await {
+                Ok(suggestion) => Some(suggestion),
+                Err(e) => {
+                    show_error.show(format!("Failed to improve the card:\n {}", e));
+                    None
+                }
+            }
+        }
+    });
+    let improving = improve.pending();
+
+    // Fields that are the same as the current ones are not shown, so accepted ones disappear.
+    let changed_fields = move || {
+        let Some(suggestion) = improve.value().get().flatten() else {
+            return Vec::new();
+        };
+        let suggested = Flashcard {
+            question: suggestion.question,
+            answer: suggestion.answer,
+            examples: Some(suggestion.examples),
+            ..card.get()
+        };
+        let card = card.get();
+        [CardField::Question, CardField::Answer, CardField::Examples]
+            .into_iter()
+            .map(|field| (field, field.get(&card), field.get(&suggested)))
+            .filter(|(_, current, suggested)| current != suggested)
+            .collect::<Vec<_>>()
+    };
+
+    view! {
+        <div class="flex flex-col gap-4 mt-4">
+            <button
+                class="self-start bg-blue-100 text-blue-700 rounded-md px-4 py-2 hover:bg-blue-200 disabled:opacity-50"
+                disabled=move || improving.get()
+                on:click=move |_| {
+                    improve.dispatch(card.get());
+                }
+            >
+                "Improve card with AI"
+            </button>
+            <Show when=move || improving.get()>
+                <Spinner />
+            </Show>
+            {move || {
+                changed_fields()
+                    .into_iter()
+                    .map(|(field, current, suggested)| {
+                        let accepted = suggested.clone();
+                        view! {
+                            <div class="border border-slate-200 rounded-lg p-4">
+                                <div class="font-semibold mb-2">{field.label()}</div>
+                                <DiffText old=current new=suggested />
+                                <button
+                                    class="bg-blue-100 text-blue-700 rounded-md px-3 py-1 text-sm hover:bg-blue-200"
+                                    on:click=move |_| on_accept.run((field, accepted.clone()))
+                                >
+                                    "Accept"
+                                </button>
+                            </div>
+                        }
+                    })
+                    .collect_view()
+            }}
+        </div>
+    }
+}
@@ -0,0 +1,116 @@
+use crate::components::error_notification::ErrorNotification;
+#[cfg(feature = "ssr")]
+use crate::db::Database;
+#[cfg(feature = "ssr")]
+use crate::duplicates::{find_duplicates, SIMILARITY_THRESHOLD};
+use crate::model::{DuplicatePair, Flashcard};
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+#[server(GetDuplicates, "/api")]
+async fn get_duplicates() -> Result<Vec<DuplicatePair>, ServerFnError> {
+    let db = Database::get_instance().unwrap().lock().unwrap();
+    let cards = db
+        .all_cards(None)
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    Ok(find_duplicates(&cards, SIMILARITY_THRESHOLD))
+}
+
+#[server(MergeCards, "/api")]
+async fn merge_cards(keep_id: i64, merge_id: i64) -> Result<(), ServerFnError> {
+    let db = Database::get_instance().unwrap().lock().unwrap();
+    db.merge_cards(keep_id, merge_id)
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+fn refresh_duplicates(
+    set_pairs: WriteSignal<Vec<DuplicatePair>>,
+    set_error: WriteSignal<Option<String>>,
+) {
+    spawn_local(async move {
+        match get_duplicates().await {
+            Ok(pairs) => set_pairs.set(pairs),
+            Err(e) => set_error.set(Some(format!("Failed to find duplicates:\n {}", e))),
+        }
+    });
+}
+
+/// Lists cards with similar questions and allows merging them.
+#[component]
+pub fn ListDuplicates() -> impl IntoView {
+    let (pairs, set_pairs) = signal(Vec::<DuplicatePair>::new());
+    let (error, set_error) = signal(None::<String>);
+
+    Effect::new(move |_| refresh_duplicates(set_pairs, set_error));
+
+    let merge = Callback::new(move |(keep_id, merge_id): (i64, i64)| {
+        spawn_local(async move {
+            match merge_cards(keep_id, merge_id).await {
+                Ok(_) => refresh_duplicates(set_pairs, set_error),
+                Err(e) => set_error.set(Some(format!("Failed to merge cards:\n {}", e))),
+            }
+        });
+    });
+
+    view! {
+        <div class="container mx-auto p-4">
+            <h1 class="text-2xl font-bold mb-4">
+                {"Duplicates: "}{move || pairs.get().len()}
+            </h1>
+            {move || {
+                pairs
+                    .get()
+                    .into_iter()
+                    .map(|pair| {
+                        let (card_id, duplicate_id) = (pair.card.id, pair.duplicate.id);
+                        view! {
+                            <div class="mb-4 bg-white border border-slate-200 rounded-lg p-4 shadow">
+                                <div class="text-slate-400 text-xs mb-2">
+                                    {format!("Similarity: {:.0}%", pair.similarity * 100.0)}
+                                </div>
+                                <div class="grid grid-cols-2 gap-4">
+                                    <DuplicateCard
+                                        card=pair.card
+                                        on_keep=move || merge.run((card_id, duplicate_id))
+                                    />
+                                    <DuplicateCard
+                                        card=pair.duplicate
+                                        on_keep=move || merge.run((duplicate_id, card_id))
+                                    />
+                                </div>
+                            </div>
+                        }
+                    })
+                    .collect_view()
+            }}
+            <ErrorNotification error=error />
+        </div>
+    }
+}
+
+#[component]
+fn DuplicateCard(
+    #[prop(into)] card: Flashcard,
+    /// Merge the other card into this one.
+    #[prop(into)]
+    on_keep: Callback<()>,
+) -> impl IntoView {
+    view! {
+        <div class="flex flex-col gap-2">
+            <a class="font-semibold" href=format!("/cards/{}", card.id)>
+                {card.question}
+            </a>
+            <div class="text-gray-700">{card.answer}</div>
+            <div class="text-slate-500 text-sm">{card.tags.join(", ")}</div>
+            <div class="text-slate-400 text-xs">
+                "Last reviewed: "{card.last_reviewed.format("%Y-%m-%d %H:%M").to_string()}
+            </div>
+            <button
+                class="self-start bg-blue-100 text-blue-700 rounded-md px-3 py-1 text-sm hover:bg-blue-200"
+                on:click=move |_| on_keep.run(())
+            >
+                "Keep this, merge the other"
+            </button>
+        </div>
+    }
+}
@@ -0,0 +1,132 @@
+use leptos::prelude::*;
+use leptos::*;
+
+use crate::components::{rich_text_warnings, RichText};
+
+/// A textarea with a formatting toolbar and a live preview of how [RichText] renders it.
+/// Setting `text` updates the textarea, so the text can be changed from outside without
+/// rerendering the form. The textarea has `name` as its id, for a `<label for=…>`: it can't be
+/// wrapped in a label, which would forward the clicks to the first toolbar button.
+#[component]
+pub fn MarkdownEditor(
+    #[prop(into)] name: String,
+    text: RwSignal<String>,
+    #[prop(default = 4)] rows: u32,
+    #[prop(optional)] required: bool,
+) -> impl IntoView {
+    let textarea = NodeRef::<html::Textarea>::new();
+    let warnings = Memo::new(move |_| rich_text_warnings(&text.get()));
+
+    let insert = move |before: &'static str, after: &'static str, placeholder: &'static str| {
+        if let Some(textarea) = textarea.get() {
+            wrap_selection(&textarea, before, after, placeholder);
+            text.set(textarea.value());
+        }
+    };
+
+    view! {
+        <div class="flex flex-col gap-1">
+            <div class="flex gap-1">
+                <ToolbarButton
+                    title="AsciiMath"
+                    label="√x"
+                    on_click=move || insert("`math ", "`", "sqrt(x)")
+                />
+                <ToolbarButton
+                    title="Code"
+                    label="</>"
+                    on_click=move || insert("```\n", "\n```", "code")
+                />
+                <ToolbarButton
+                    title="Cloze deletion"
+                    label="[...]"
+                    on_click=move || insert("{{c1::", "}}", "hidden text")
+                />
+            </div>
+            <div class="grid grid-cols-2 gap-2">
+                <textarea
+                    class="border rounded px-3 py-2 font-mono text-sm"
+                    id=name.clone()
+                    name=name
+                    rows=rows
+                    required=required
+                    node_ref=textarea
+                    prop:value=move || text.get()
+                    on:input=move |ev| text.set(event_target_value(&ev))
+                >
+                    {text.get_untracked()}
+                </textarea>
+                <div class="border rounded px-3 py-2 bg-gray-50 overflow-auto">
+                    {move || view! { <RichText text=text.get() /> }}
+                </div>
+            </div>
+            <For
+                each=move || warnings.get()
+                key=|warning| warning.clone()
+                children=move |warning| {
+                    view! { <div class="text-sm text-amber-600">{warning}</div> }
+                }
+            />
+        </div>
+    }
+}
+
+#[component]
+fn ToolbarButton(
+    #[prop(into)] title: String,
+    #[prop(into)] label: String,
+    #[prop(into)] on_click: Callback<()>,
+) -> impl IntoView {
+    view! {
+        <button
+            class="border rounded px-2 py-0.5 text-sm text-gray-600 hover:bg-gray-100"
+            type="button"
+            title=title
+            on:click=move |_| on_click.run(())
+        >
+            {label}
+        </button>
+    }
+}
+
+/// Wraps the selected text (or the placeholder, if nothing is selected) with `before` and `after`.
+/// The wrapped text stays selected so that it can be typed over.
+fn wrap_selection(
+    textarea: &web_sys::HtmlTextAreaElement,
+    before: &str,
+    after: &str,
+    placeholder: &str,
+) {
+    // Selection offsets are in UTF-16 code units.
+    let value: Vec<u16> = textarea.value().encode_utf16().collect();
+    let start = textarea
+        .selection_start()
+        .ok()
+        .flatten()
+        .map_or(value.len(), |s| (s as usize).min(value.len()));
+    let end = textarea
+        .selection_end()
+        .ok()
+        .flatten()
+        .map_or(start, |e| (e as usize).clamp(start, value.len()));
+
+    let selected = String::from_utf16_lossy(&value[start..end]);
+    let inner = if selected.is_empty() {
+        placeholder.to_string()
+    } else {
+        selected
+    };
+    textarea.set_value(&format!(
+        "{}{}{}{}{}",
+        String::from_utf16_lossy(&value[..start]),
+        before,
+        inner,
+        after,
+        String::from_utf16_lossy(&value[end..]),
+    ));
+
+    let inner_start = start + before.encode_utf16().count();
+    let inner_end = inner_start + inner.encode_utf16().count();
+    let _ = textarea.set_selection_range(inner_start as u32, inner_end as u32);
+    let _ = textarea.focus();
+}
@@ -1,15 +1,28 @@
+mod diagram;
 mod richtext;
 mod show_error;
 
 pub mod add_card;
+pub mod answer_grader;
+pub mod card_history;
 pub mod edit_card;
 pub mod error_notification;
 pub mod flashcard;
+pub mod generate_cards;
+pub mod improve_card;
 pub mod list_cards;
+pub mod list_duplicates;
 pub mod markdown;
+pub mod markdown_editor;
+pub mod multiple_choice;
 pub mod review_by_tag;
 pub mod review_cards;
+pub mod typed_answer;
 pub mod view_card;
 
-pub use richtext::RichText;
+#[cfg(feature = "ssr")]
+pub(crate) use diagram::DiagramKind;
+#[cfg(feature = "ssr")]
+pub(crate) use richtext::{parse_rich_text, TextBlock};
+pub use richtext::{rich_text_warnings, RichText};
 pub use show_error::ShowError;
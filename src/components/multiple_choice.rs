@@ -0,0 +1,157 @@
+use leptos::prelude::*;
+use thaw::Spinner;
+
+#[cfg(feature = "ssr")]
+use crate::assistant::Assistant;
+#[cfg(feature = "ssr")]
+use crate::choices::{choice_set, distractors, CHOICES};
+use crate::components::{RichText, ShowError};
+#[cfg(feature = "ssr")]
+use crate::db::Database;
+use crate::errors::AppError;
+#[cfg(feature = "ssr")]
+use crate::languages::progress::record_card_review;
+use crate::model::ChoiceSet;
+#[cfg(feature = "ssr")]
+use crate::model::Grade;
+
+/// Options for the card: answers of the cards with the same tags, topped up by the LLM
+/// when there are not enough of them.
+#[server(GetChoiceSet, "/api")]
+async fn get_choice_set(card_id: i64) -> Result<ChoiceSet, AppError> {
+    // Don't hold the database lock while waiting for the LLM.
+    let (card, cards) = {
+        let db = Database::get_instance().unwrap().lock().unwrap();
+        let card = db
+            .get_card(card_id)
+            .map_err(|e| AppError::DuckdbError(e.to_string()))?;
+        let mut cards = Vec::new();
+        for tag in card.tags.iter() {
+            cards.extend(db.all_cards(Some(tag.clone()))?);
+        }
+        (card, cards)
+    };
+
+    let mut wrong = distractors(&card, &cards, CHOICES - 1);
+    if wrong.len() < CHOICES - 1 {
+        match Assistant::from_settings()
+            .distractors(&card, CHOICES - 1 - wrong.len(), &wrong)
+            .await
+        {
+            Ok(generated) => wrong.extend(generated),
+            // Fewer options are still a review.
+            Err(e) => log::warn!("No distractors for card #{}: {}", card_id, e),
+        }
+    }
+    Ok(choice_set(&card, wrong, &mut rand::rng()))
+}
+
+/// Records the pick as a review: "good" for the right answer, "again" otherwise.
+/// Returns whether it's right.
+#[server(SubmitChoice, "/api")]
+async fn submit_choice(card_id: i64, choice: String) -> Result<bool, AppError> {
+    let card = {
+        let db = Database::get_instance().unwrap().lock().unwrap();
+        db.get_card(card_id)
+            .map_err(|e| AppError::DuckdbError(e.to_string()))?
+    };
+    let grade = if choice == card.answer {
+        Grade::Good
+    } else {
+        Grade::Again
+    };
+    {
+        let db = Database::get_instance().unwrap().lock().unwrap();
+        db.review(card_id, grade)
+            .map_err(|e| AppError::DuckdbError(e.to_string()))?;
+    }
+    record_card_review(&card, grade.remembered());
+    Ok(grade.remembered())
+}
+
+/// Instead of self-grading, the answer is picked among the answers of similar cards.
+#[component]
+pub fn MultipleChoice(
+    card_id: i64,
+    /// Called once the answer is picked, so that the correct answer can be shown.
+    #[prop(into)]
+    on_answered: Callback<()>,
+    /// Move on to the next card.
+    #[prop(into)]
+    on_next: Callback<()>,
+) -> impl IntoView {
+    let show_error = ShowError::from_ctx();
+    let choices = Resource::new(|| (), move |_| get_choice_set(card_id));
+    let picked = RwSignal::new(None::<(usize, bool)>);
+
+    let submit = Action::new(move |input: &(usize, String)| {
+        let (index, choice) = input.clone();
+        async move {
+            match submit_choice(card_id, choice).await {
+                Ok(right) => {
+                    picked.set(Some((index, right)));
+                    on_answered.run(());
+                }
+                Err(e) => show_error.show(format!("Failed to submit the answer:\n {}", e)),
+            }
+        }
+    });
+
+    view! {
+        <div class="flex flex-col gap-4 w-full">
+            <Transition fallback=move || {
+                view! { <Spinner /> }
+            }>
+                {move || Suspend::new(async move {
+                    match choices.await {
+                        Ok(ChoiceSet { options, .. }) => {
+                            options
+                                .into_iter()
+                                .enumerate()
+                                .map(|(index, option)| {
+                                    let class = move || {
+                                        match picked.get() {
+                                            Some((i, true)) if i == index => "bg-green-100 border-green-400",
+                                            Some((i, false)) if i == index => "bg-red-100 border-red-400",
+                                            _ => "bg-white hover:bg-slate-50",
+                                        }
+                                    };
+                                    let choice = option.clone();
+                                    view! {
+                                        <button
+                                            class=move || {
+                                                format!(
+                                                    "text-left border rounded-md px-4 py-3 cursor-pointer disabled:cursor-default {}",
+                                                    class(),
+                                                )
+                                            }
+                                            disabled=move || picked.get().is_some() || submit.pending().get()
+                                            on:click=move |_| {
+                                                submit.dispatch((index, choice.clone()));
+                                            }
+                                        >
+                                            <RichText text=option />
+                                        </button>
+                                    }
+                                })
+                                .collect_view()
+                                .into_any()
+                        }
+                        Err(e) => {
+                            view! { <p class="text-red-600">{format!("Failed to load the options: {}", e)}</p> }
+                                .into_any()
+                        }
+                    }
+                })}
+            </Transition>
+            <Show when=move || picked.get().is_some()>
+                <button
+                    class="self-center bg-blue-100 text-blue-700 border-none rounded-md px-6 py-3 text-base cursor-pointer transition-colors hover:bg-blue-200"
+                    on:click=move |_| on_next.run(())
+                >
+                    "Next"
+                </button>
+            </Show>
+        </div>
+    }
+}
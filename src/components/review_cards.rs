@@ -2,6 +2,9 @@ use crate::components::error_notification::ErrorNotification;
 use crate::components::flashcard::Flashcard;
 #[cfg(feature = "ssr")]
 use crate::db::Database;
+use crate::languages::model::is_conjugation_drill;
+#[cfg(feature = "ssr")]
+use crate::languages::progress::record_card_review;
 use crate::model;
 use leptos::prelude::*;
 use leptos::task::spawn_local;
@@ -15,16 +18,21 @@ async fn get_cards() -> Result<Vec<model::Flashcard>, ServerFnError> {
 
 #[server(SubmitAnswer, "/api")]
 pub async fn submit_answer(card_id: i64, remembered: bool) -> Result<(), ServerFnError> {
-    let db = Database::get_instance().unwrap().lock().unwrap();
+    let card = {
+        let db = Database::get_instance().unwrap().lock().unwrap();
 
-    if remembered {
-        db.ok(card_id)
-            .map_err(|e| ServerFnError::new(e.to_string()))?;
-    } else {
-        db.fail(card_id)
-            .map_err(|e| ServerFnError::new(e.to_string()))?;
-    }
+        if remembered {
+            db.ok(card_id)
+                .map_err(|e| ServerFnError::new(e.to_string()))?;
+        } else {
+            db.fail(card_id)
+                .map_err(|e| ServerFnError::new(e.to_string()))?;
+        }
+        db.get_card(card_id)
+            .map_err(|e| ServerFnError::new(e.to_string()))?
+    };
 
+    record_card_review(&card, remembered);
     Ok(())
 }
 
@@ -55,11 +63,57 @@ pub fn ReviewAllCards() -> impl IntoView {
     }
 }
 
+/// How the answers are given while reviewing.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum ReviewMode {
+    /// Show the answer and tell whether I remembered it.
+    #[default]
+    SelfGraded,
+    /// Type the answer and let an LLM grade it.
+    AiGraded,
+    /// Pick the answer among the answers of similar cards.
+    MultipleChoice,
+    /// Type the exact answer, e.g. the form of a verb.
+    Typed,
+}
+
+impl ReviewMode {
+    pub const ALL: [ReviewMode; 4] = [
+        ReviewMode::SelfGraded,
+        ReviewMode::AiGraded,
+        ReviewMode::MultipleChoice,
+        ReviewMode::Typed,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReviewMode::SelfGraded => "Show the answer",
+            ReviewMode::AiGraded => "Type answers and let AI grade them",
+            ReviewMode::MultipleChoice => "Multiple choice",
+            ReviewMode::Typed => "Type the exact answers",
+        }
+    }
+
+    /// Conjugation drills are checked exactly, there's nothing for AI to grade.
+    fn for_cards(cards: &[model::Flashcard]) -> Self {
+        let drills = !cards.is_empty() && cards.iter().all(is_conjugation_drill);
+        if drills {
+            ReviewMode::Typed
+        } else {
+            ReviewMode::default()
+        }
+    }
+}
+
 /// A reusable component to review a given list of cards.
 #[component]
 pub fn ReviewCards(#[prop(into)] cards: Signal<Vec<model::Flashcard>>) -> impl IntoView {
     let current_index = RwSignal::new(0usize);
     let (error, set_error) = signal(None::<String>);
+    let mode = RwSignal::new(ReviewMode::default());
+    // Until another mode is picked.
+    Effect::new(move |_| mode.set(ReviewMode::for_cards(&cards.get())));
+    let next_card = Callback::new(move |_: ()| current_index.update(|i| *i += 1));
 
     let handle_answer = Callback::new(move |answer: model::FlashcardAnswer| {
         let remembered = matches!(answer, model::FlashcardAnswer::Remember);
@@ -91,6 +145,28 @@ pub fn ReviewCards(#[prop(into)] cards: Signal<Vec<model::Flashcard>>) -> impl I
                 }
                 max=move || cards.get().len()
             ></progress>
+            <label class="flex items-center justify-end gap-2 text-sm text-slate-600 mt-2">
+                "Review mode"
+                <select
+                    class="border rounded px-2 py-1"
+                    on:change=move |ev| {
+                        let index = event_target_value(&ev).parse::<usize>().unwrap_or_default();
+                        mode.set(ReviewMode::ALL[index]);
+                    }
+                >
+                    {ReviewMode::ALL
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, option)| {
+                            view! {
+                                <option value=index.to_string() selected=move || mode.get() == option>
+                                    {option.label()}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+            </label>
             <Show
                 when=move || { cards.get().get(current_index.get()).is_some() }
                 fallback=|| {
@@ -103,7 +179,14 @@ pub fn ReviewCards(#[prop(into)] cards: Signal<Vec<model::Flashcard>>) -> impl I
             >
                 {move || {
                     let card = cards.get().get(current_index.get()).cloned().unwrap();
-                    view! { <Flashcard card=card.clone() on_answer=handle_answer /> }
+                    view! {
+                        <Flashcard
+                            card=card.clone()
+                            on_answer=handle_answer
+                            mode=mode.get()
+                            on_next=Some(next_card)
+                        />
+                    }
                 }}
             </Show>
             <ErrorNotification error=error />
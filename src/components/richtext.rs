@@ -1,5 +1,8 @@
+#[cfg(feature = "ssr")]
+use latex2mathml::{latex_to_mathml, DisplayStyle};
 use leptos::prelude::*;
 
+use crate::components::diagram::{Diagram, DiagramKind};
 use crate::components::markdown::Markdown;
 
 /// Rich text is composed of a series of text blocks.
@@ -9,12 +12,30 @@ pub enum TextBlock {
     /// Just a string.
     Raw(String),
     AsciiMath(String),
+    /// `$...$` is inline, `$$...$$` is display math.
+    Latex {
+        tex: String,
+        display: bool,
+    },
+    /// A fenced code block with a diagram, e.g. ```` ```dot ````.
+    Diagram {
+        kind: DiagramKind,
+        source: String,
+    },
+    /// `{{c1::text}}` or `{{c1::text::hint}}`: the text is hidden until it's revealed.
+    Cloze {
+        text: String,
+        hint: Option<String>,
+    },
 }
 
 impl TextBlock {
     pub fn is_empty(&self) -> bool {
         match self {
             TextBlock::Raw(text) | TextBlock::AsciiMath(text) => text.is_empty(),
+            TextBlock::Latex { tex, .. } => tex.is_empty(),
+            TextBlock::Diagram { source, .. } => source.is_empty(),
+            TextBlock::Cloze { text, .. } => text.is_empty(),
         }
     }
 }
@@ -27,7 +48,63 @@ pub fn AsciiMath(input: String) -> impl IntoView {
     view! { <span inner_html=math_ml></span> }
 }
 
-/// Renders AsciiMath and Markdown.
+/// Render a LaTeX expression as MathML.
+/// Expressions that fail to parse are shown as is.
+/// The MathML is rendered on the server, so that LaTeX support stays out of the WASM bundle.
+#[component]
+pub fn Latex(input: String, display: bool) -> impl IntoView {
+    #[cfg(feature = "ssr")]
+    let html = latex_html(&input, display);
+    // Only shown once it's rendered: hydration keeps the HTML from the server in the meantime.
+    #[cfg(not(feature = "ssr"))]
+    let html = {
+        let html = LocalResource::new(move || render_latex(input.clone(), display));
+        move || html.get().and_then(Result::ok).unwrap_or_default()
+    };
+    view! { <span inner_html=html></span> }
+}
+
+#[server(RenderLatex, "/api")]
+async fn render_latex(input: String, display: bool) -> Result<String, ServerFnError> {
+    Ok(latex_html(&input, display))
+}
+
+#[cfg(feature = "ssr")]
+fn latex_html(input: &str, display: bool) -> String {
+    let style = if display {
+        DisplayStyle::Block
+    } else {
+        DisplayStyle::Inline
+    };
+    latex_to_mathml(input, style).unwrap_or_else(|_| format!("<code>{}</code>", escape_html(input)))
+}
+
+/// Escapes text for HTML that is set with `inner_html`.
+#[cfg(feature = "ssr")]
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A cloze deletion, shown as `[...]` or `[hint]` until it's clicked.
+#[component]
+pub fn Cloze(text: String, hint: Option<String>) -> impl IntoView {
+    let revealed = RwSignal::new(false);
+    let hidden = format!("[{}]", hint.unwrap_or_else(|| "...".to_string()));
+    view! {
+        <button
+            class="px-1 rounded bg-blue-100 text-blue-800"
+            type="button"
+            on:click=move |_| revealed.update(|revealed| *revealed = !*revealed)
+        >
+            {move || if revealed.get() { text.clone() } else { hidden.clone() }}
+        </button>
+    }
+}
+
+/// Renders AsciiMath, LaTeX, diagrams, cloze deletions and Markdown.
 #[component]
 pub fn RichText(#[prop(into)] text: String) -> impl IntoView {
     parse_rich_text(&text)
@@ -37,27 +114,15 @@ pub fn RichText(#[prop(into)] text: String) -> impl IntoView {
                 view! { <Markdown text=Memo::new(move |_| text.clone()) /> }.into_any()
             }
             TextBlock::AsciiMath(text) => view! { <AsciiMath input=text /> }.into_any(),
-        })
-        .collect_view()
-}
-
-#[derive(Debug)]
-enum ParseState {
-    RawStr(usize, usize),
-    InAsciiMath(usize, usize),
-}
-
-impl ParseState {
-    fn text_block(&self, text: &str) -> TextBlock {
-        match self {
-            ParseState::RawStr(start, end) => {
-                TextBlock::Raw(text.chars().skip(*start).take(*end - *start).collect())
+            TextBlock::Latex { tex, display } => {
+                view! { <Latex input=tex display=display /> }.into_any()
             }
-            ParseState::InAsciiMath(start, end) => {
-                TextBlock::AsciiMath(text.chars().skip(*start).take(*end - *start).collect())
+            TextBlock::Diagram { kind, source } => {
+                view! { <Diagram kind=kind source=source /> }.into_any()
             }
-        }
-    }
+            TextBlock::Cloze { text, hint } => view! { <Cloze text=text hint=hint /> }.into_any(),
+        })
+        .collect_view()
 }
 
 #[derive(Debug, PartialEq)]
@@ -70,37 +135,80 @@ enum Parsed {
 /// ```md
 /// * Markdown
 /// * AsciiMath: `math 1/3`
+/// * LaTeX: $\frac{1}{3}$ or $$\sum_{i=1}^n i$$
+/// * Diagrams: fenced code blocks with `dot` or `mermaid` info string
+/// * Cloze deletions: {{c1::hidden text}} or {{c1::hidden text::hint}}
 /// ```
-fn parse_rich_text(text: &str) -> Vec<TextBlock> {
+///
+/// Dollars in code spans and escaped dollars (`\$`) are left to Markdown.
+pub(crate) fn parse_rich_text(text: &str) -> Vec<TextBlock> {
+    let chars: Vec<char> = text.chars().collect();
     let mut text_blocks = Vec::new();
-    let mut state = ParseState::RawStr(0, 0);
+    let mut raw_start = 0;
+    let mut i = 0;
 
-    let mut char_iter = text.chars().enumerate().peekable();
-    while let Some((i, c)) = char_iter.peek().cloned() {
-        match state {
-            ParseState::RawStr(start, end) => match match_str("`math ", &mut char_iter) {
+    while i < chars.len() {
+        let (block, block_end) = match chars[i] {
+            '\\' => {
+                // Skip the escaped character, Markdown will unescape it.
+                i += 2;
+                continue;
+            }
+            '`' => match match_str("`math ", &mut chars[i..].iter().copied().enumerate()) {
                 Parsed::Yes(parsed_chars) => {
-                    text_blocks.push(state.text_block(text));
-                    state = ParseState::InAsciiMath(end + parsed_chars, end + parsed_chars);
-                }
-                Parsed::No(parsed_char) => {
-                    state = ParseState::RawStr(start, end + parsed_char);
+                    let start = i + parsed_chars;
+                    let end = find_char(&chars, start, '`').unwrap_or(chars.len());
+                    (TextBlock::AsciiMath(collect(&chars, start, end)), end + 1)
                 }
+                Parsed::No(_) => match parse_diagram(&chars, i) {
+                    Some(diagram) => diagram,
+                    None => {
+                        i = skip_code_span(&chars, i);
+                        continue;
+                    }
+                },
             },
-            ParseState::InAsciiMath(start, _) => {
-                // Consume the peeked character.
-                char_iter.next();
-                if c == '`' {
-                    text_blocks.push(state.text_block(text));
-                    state = ParseState::RawStr(i + 1, i + 1);
-                } else {
-                    state = ParseState::InAsciiMath(start, i + 1);
+            '$' => {
+                let display = chars.get(i + 1) == Some(&'$');
+                let delim_len = if display { 2 } else { 1 };
+                let start = i + delim_len;
+                match find_closing_dollar(&chars, start, display) {
+                    Some(end) => (
+                        TextBlock::Latex {
+                            tex: collect(&chars, start, end).trim().to_string(),
+                            display,
+                        },
+                        end + delim_len,
+                    ),
+                    None => {
+                        i += delim_len;
+                        continue;
+                    }
+                }
+            }
+            '{' => match parse_cloze(&chars, i) {
+                Some(cloze) => cloze,
+                None => {
+                    i += 1;
+                    continue;
                 }
+            },
+            _ => {
+                i += 1;
+                continue;
             }
+        };
+
+        let raw_block = TextBlock::Raw(collect(&chars, raw_start, i));
+        if !raw_block.is_empty() {
+            text_blocks.push(raw_block);
         }
+        text_blocks.push(block);
+        i = block_end;
+        raw_start = i;
     }
 
-    let last_block = state.text_block(text);
+    let last_block = TextBlock::Raw(collect(&chars, raw_start, chars.len()));
     if !last_block.is_empty() {
         text_blocks.push(last_block);
     }
@@ -108,6 +216,187 @@ fn parse_rich_text(text: &str) -> Vec<TextBlock> {
     text_blocks
 }
 
+/// Finds mistakes that would render differently than intended, e.g. an unterminated math block.
+pub fn rich_text_warnings(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut warnings = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            '`' => match match_str("`math ", &mut chars[i..].iter().copied().enumerate()) {
+                Parsed::Yes(parsed_chars) => {
+                    let start = i + parsed_chars;
+                    match find_char(&chars, start, '`') {
+                        Some(end) => {
+                            if collect(&chars, start, end).trim().is_empty() {
+                                warnings.push(format!("Empty `math` block at character {}", i));
+                            }
+                            i = end + 1;
+                        }
+                        None => {
+                            warnings.push(format!("Unterminated `math` block at character {}", i));
+                            i = chars.len();
+                        }
+                    }
+                }
+                Parsed::No(_) => i = skip_code_span(&chars, i),
+            },
+            '$' if chars.get(i + 1) == Some(&'$') => match find_closing_dollar(&chars, i + 2, true)
+            {
+                Some(end) => i = end + 2,
+                None => {
+                    warnings.push(format!("Unterminated $$ block at character {}", i));
+                    i += 2;
+                }
+            },
+            '{' => match cloze_text_start(&chars, i) {
+                Some(start) => match find_cloze_end(&chars, start) {
+                    Some(end) => {
+                        if collect(&chars, start, end).trim().is_empty() {
+                            warnings.push(format!("Empty cloze at character {}", i));
+                        }
+                        i = end + 2;
+                    }
+                    None => {
+                        warnings.push(format!("Unterminated cloze at character {}", i));
+                        i = chars.len();
+                    }
+                },
+                None => i += 1,
+            },
+            _ => i += 1,
+        }
+    }
+
+    warnings
+}
+
+fn collect(chars: &[char], start: usize, end: usize) -> String {
+    chars[start.min(chars.len())..end.min(chars.len())]
+        .iter()
+        .collect()
+}
+
+fn find_char(chars: &[char], from: usize, c: char) -> Option<usize> {
+    (from..chars.len()).find(|&i| chars[i] == c)
+}
+
+/// Parses a fenced code block with a diagram that starts at `start`.
+///
+/// Returns the diagram and the index right after the code block.
+fn parse_diagram(chars: &[char], start: usize) -> Option<(TextBlock, usize)> {
+    let ticks = backtick_run(chars, start);
+    if ticks < 3 {
+        return None;
+    }
+    let info_end = find_char(chars, start + ticks, '\n')?;
+    let kind = DiagramKind::from_info_string(collect(chars, start + ticks, info_end).trim())?;
+    let end = find_closing_backticks(chars, info_end + 1, ticks)?;
+    let source = collect(chars, info_end + 1, end);
+    Some((TextBlock::Diagram { kind, source }, end + ticks))
+}
+
+/// Parses a cloze deletion that starts at `start`.
+///
+/// Returns the cloze and the index right after its closing `}}`.
+fn parse_cloze(chars: &[char], start: usize) -> Option<(TextBlock, usize)> {
+    let text_start = cloze_text_start(chars, start)?;
+    let end = find_cloze_end(chars, text_start)?;
+    let text = collect(chars, text_start, end);
+    let (text, hint) = match text.split_once("::") {
+        Some((text, hint)) => (text.to_string(), Some(hint.to_string())),
+        None => (text, None),
+    };
+    let hint = hint.filter(|hint| !hint.trim().is_empty());
+    Some((TextBlock::Cloze { text, hint }, end + 2))
+}
+
+/// Returns the index right after `{{c<number>::` if it's at `start`.
+fn cloze_text_start(chars: &[char], start: usize) -> Option<usize> {
+    if collect(chars, start, start + 3) != "{{c" {
+        return None;
+    }
+    let digits = chars[start + 3..]
+        .iter()
+        .take_while(|c| c.is_ascii_digit())
+        .count();
+    let i = start + 3 + digits;
+    (digits > 0 && collect(chars, i, i + 2) == "::").then_some(i + 2)
+}
+
+fn find_cloze_end(chars: &[char], from: usize) -> Option<usize> {
+    (from..chars.len().saturating_sub(1)).find(|&i| chars[i] == '}' && chars[i + 1] == '}')
+}
+
+/// Skips an inline code span or a fenced code block that starts at `start`.
+/// It ends with the same number of backticks it was opened with.
+///
+/// Returns the index right after the code span.
+fn skip_code_span(chars: &[char], start: usize) -> usize {
+    let ticks = backtick_run(chars, start);
+    match find_closing_backticks(chars, start + ticks, ticks) {
+        Some(end) => end + ticks,
+        // Unterminated code span: the backticks are literal.
+        None => start + ticks,
+    }
+}
+
+fn find_closing_backticks(chars: &[char], from: usize, ticks: usize) -> Option<usize> {
+    let mut i = from;
+    while i < chars.len() {
+        if chars[i] == '`' {
+            let run = backtick_run(chars, i);
+            if run == ticks {
+                return Some(i);
+            }
+            i += run;
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
+
+fn backtick_run(chars: &[char], start: usize) -> usize {
+    chars[start..].iter().take_while(|&&c| c == '`').count()
+}
+
+/// Finds the closing `$` (or `$$` in display mode) of a LaTeX expression.
+///
+/// Inline math follows the Pandoc rules so that prices like "$5 and $10" stay text:
+/// the opening `$` must be followed by non-whitespace, the closing `$` must be preceded
+/// by non-whitespace and must not be followed by a digit.
+fn find_closing_dollar(chars: &[char], start: usize, display: bool) -> Option<usize> {
+    if !display && chars.get(start).is_none_or(|c| c.is_whitespace()) {
+        return None;
+    }
+
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            '$' if display => {
+                if chars.get(i + 1) == Some(&'$') && i > start {
+                    return Some(i);
+                }
+                return None;
+            }
+            '$' => {
+                let after_space = chars[i - 1].is_whitespace();
+                let before_digit = chars.get(i + 1).is_some_and(|c| c.is_ascii_digit());
+                if i > start && !after_space && !before_digit {
+                    return Some(i);
+                }
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
 fn match_str(s: &str, char_iter: &mut impl Iterator<Item = (usize, char)>) -> Parsed {
     let mut match_chars = s.chars().enumerate();
     while let Some((i, c)) = match_chars.next() {
@@ -124,7 +413,10 @@ fn match_str(s: &str, char_iter: &mut impl Iterator<Item = (usize, char)>) -> Pa
 
 #[cfg(test)]
 mod tests {
-    use super::{match_str, parse_rich_text, Parsed, TextBlock};
+    #[cfg(feature = "ssr")]
+    use super::escape_html;
+    use super::{match_str, parse_rich_text, rich_text_warnings, Parsed, TextBlock};
+    use crate::components::diagram::DiagramKind;
 
     #[test]
     fn test_match_str() {
@@ -149,4 +441,141 @@ mod tests {
         assert_eq!(blocks[1], TextBlock::AsciiMath("0.bar(3)".to_string()));
         assert_eq!(blocks[2], TextBlock::Raw(" ?".to_string()));
     }
+
+    #[test]
+    fn test_parse_rich_text_latex() {
+        let blocks = parse_rich_text("Inline $x^2$ and display $$\\frac{1}{2}$$.");
+        assert_eq!(blocks.len(), 5);
+        assert_eq!(blocks[0], TextBlock::Raw("Inline ".to_string()));
+        assert_eq!(
+            blocks[1],
+            TextBlock::Latex {
+                tex: "x^2".to_string(),
+                display: false
+            }
+        );
+        assert_eq!(blocks[2], TextBlock::Raw(" and display ".to_string()));
+        assert_eq!(
+            blocks[3],
+            TextBlock::Latex {
+                tex: "\\frac{1}{2}".to_string(),
+                display: true
+            }
+        );
+        assert_eq!(blocks[4], TextBlock::Raw(".".to_string()));
+    }
+
+    #[test]
+    fn test_parse_rich_text_ignores_escaped_dollars_and_prices() {
+        let blocks = parse_rich_text("It costs \\$5, not \\$10.");
+        assert_eq!(
+            blocks,
+            vec![TextBlock::Raw("It costs \\$5, not \\$10.".to_string())]
+        );
+
+        let blocks = parse_rich_text("Between $5 and $10.");
+        assert_eq!(
+            blocks,
+            vec![TextBlock::Raw("Between $5 and $10.".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_rich_text_ignores_dollars_in_code() {
+        let text = "Run `echo $HOME $PATH` and:\n```sh\nexport A=$B$\n```";
+        let blocks = parse_rich_text(text);
+        assert_eq!(blocks, vec![TextBlock::Raw(text.to_string())]);
+    }
+
+    #[test]
+    fn test_parse_rich_text_mixed_math() {
+        let blocks = parse_rich_text("`math 1/3` vs $\\frac{1}{3}$");
+        assert_eq!(
+            blocks,
+            vec![
+                TextBlock::AsciiMath("1/3".to_string()),
+                TextBlock::Raw(" vs ".to_string()),
+                TextBlock::Latex {
+                    tex: "\\frac{1}{3}".to_string(),
+                    display: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_rich_text_diagrams() {
+        let blocks =
+            parse_rich_text("Graph:\n```dot\ndigraph { a -> b }\n```\n```rust\nlet a = 1;\n```");
+        assert_eq!(
+            blocks,
+            vec![
+                TextBlock::Raw("Graph:\n".to_string()),
+                TextBlock::Diagram {
+                    kind: DiagramKind::Dot,
+                    source: "digraph { a -> b }\n".to_string()
+                },
+                TextBlock::Raw("\n```rust\nlet a = 1;\n```".to_string()),
+            ]
+        );
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html(r#"<a href="x">&</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_parse_rich_text_cloze() {
+        let blocks = parse_rich_text("The capital of {{c1::France}} is {{c2::Paris::city}}.");
+        assert_eq!(
+            blocks,
+            vec![
+                TextBlock::Raw("The capital of ".to_string()),
+                TextBlock::Cloze {
+                    text: "France".to_string(),
+                    hint: None
+                },
+                TextBlock::Raw(" is ".to_string()),
+                TextBlock::Cloze {
+                    text: "Paris".to_string(),
+                    hint: Some("city".to_string())
+                },
+                TextBlock::Raw(".".to_string()),
+            ]
+        );
+
+        let text = "Not a cloze: {{c::x}}, {{c1:x}} and `{{c1::code}}`";
+        assert_eq!(
+            parse_rich_text(text),
+            vec![TextBlock::Raw(text.to_string())]
+        );
+    }
+
+    #[test]
+    fn test_rich_text_warnings() {
+        assert!(rich_text_warnings("`math 1/3` and $$x$$ and `code").is_empty());
+        assert_eq!(
+            rich_text_warnings("What is `math 1/3 ?"),
+            vec!["Unterminated `math` block at character 8".to_string()]
+        );
+        assert_eq!(
+            rich_text_warnings("`math ` and $$x"),
+            vec![
+                "Empty `math` block at character 0".to_string(),
+                "Unterminated $$ block at character 12".to_string(),
+            ]
+        );
+        assert_eq!(
+            rich_text_warnings("{{c1:: }} and {{c2::Paris"),
+            vec![
+                "Empty cloze at character 0".to_string(),
+                "Unterminated cloze at character 14".to_string(),
+            ]
+        );
+    }
 }
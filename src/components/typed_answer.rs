@@ -0,0 +1,110 @@
+use leptos::prelude::*;
+
+#[cfg(feature = "ssr")]
+use crate::choices::typed_answer_grade;
+use crate::components::ShowError;
+#[cfg(feature = "ssr")]
+use crate::db::Database;
+use crate::errors::AppError;
+#[cfg(feature = "ssr")]
+use crate::languages::progress::record_card_review;
+use crate::model::Grade;
+
+/// Compares the typed answer with the answer of the card and records the review.
+#[server(CheckTypedAnswer, "/api")]
+async fn check_typed_answer(card_id: i64, answer: String) -> Result<Grade, AppError> {
+    let (card, grade) = {
+        let db = Database::get_instance().unwrap().lock().unwrap();
+        let card = db
+            .get_card(card_id)
+            .map_err(|e| AppError::DuckdbError(e.to_string()))?;
+        let grade = typed_answer_grade(&answer, &card.answer);
+        db.review(card_id, grade)
+            .map_err(|e| AppError::DuckdbError(e.to_string()))?;
+        (card, grade)
+    };
+    record_card_review(&card, grade.remembered());
+    Ok(grade)
+}
+
+/// The answer is typed and must be the exact answer of the card, e.g. a verb form.
+#[component]
+pub fn TypedAnswer(
+    card_id: i64,
+    /// Called once the answer is checked, so that the correct answer can be shown.
+    #[prop(into)]
+    on_checked: Callback<()>,
+    /// Move on to the next card.
+    #[prop(into)]
+    on_next: Callback<()>,
+) -> impl IntoView {
+    let show_error = ShowError::from_ctx();
+    let answer = RwSignal::new(String::new());
+
+    let check = Action::new(move |answer: &String| {
+        let answer = answer.clone();
+        async move {
+            match check_typed_answer(card_id, answer).await {
+                Ok(grade) => {
+                    on_checked.run(());
+                    Some(grade)
+                }
+                Err(e) => {
+                    show_error.show(format!("Failed to check the answer:\n {}", e));
+                    None
+                }
+            }
+        }
+    });
+    let checking = check.pending();
+    let result = move || check.value().get().flatten();
+    let submit = move || {
+        if !checking.get_untracked() && !answer.get_untracked().trim().is_empty() {
+            check.dispatch(answer.get_untracked());
+        }
+    };
+
+    view! {
+        <div class="flex flex-col gap-4 w-full">
+            <Show
+                when=move || result().is_none()
+                fallback=move || {
+                    let right = result().is_some_and(|grade| grade.remembered());
+                    view! {
+                        <p class=if right {
+                            "text-center font-semibold text-green-700"
+                        } else {
+                            "text-center font-semibold text-red-600"
+                        }>{if right { "Right" } else { "Wrong" }}</p>
+                        <button
+                            class="self-center bg-blue-100 text-blue-700 border-none rounded-md px-6 py-3 text-base cursor-pointer transition-colors hover:bg-blue-200"
+                            on:click=move |_| on_next.run(())
+                        >
+                            "Next"
+                        </button>
+                    }
+                }
+            >
+                <input
+                    class="border rounded px-3 py-2"
+                    type="text"
+                    placeholder="Type your answer"
+                    prop:value=move || answer.get()
+                    on:input=move |ev| answer.set(event_target_value(&ev))
+                    on:keydown=move |ev| {
+                        if ev.key() == "Enter" {
+                            submit();
+                        }
+                    }
+                />
+                <button
+                    class="self-center bg-blue-100 text-blue-700 border-none rounded-md px-6 py-3 text-base cursor-pointer transition-colors hover:bg-blue-200 disabled:opacity-50"
+                    disabled=move || checking.get() || answer.get().trim().is_empty()
+                    on:click=move |_| submit()
+                >
+                    "Check answer"
+                </button>
+            </Show>
+        </div>
+    }
+}
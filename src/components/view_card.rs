@@ -1,3 +1,4 @@
+use crate::components::card_history::CardHistory;
 use crate::components::edit_card::get_card;
 use crate::components::flashcard::Flashcard;
 use crate::model;
@@ -15,6 +16,9 @@ struct ViewCardParams {
 #[component]
 pub fn ViewCard() -> impl IntoView {
     let (card, set_card) = signal(None::<model::Flashcard>);
+    let show_history = RwSignal::new(false);
+    // Bumped to load the card again, e.g. after restoring a previous version.
+    let reload = RwSignal::new(0);
 
     let params = use_params::<ViewCardParams>();
     let id = move || {
@@ -28,6 +32,7 @@ pub fn ViewCard() -> impl IntoView {
 
     // Load card data
     Effect::new(move |_| {
+        reload.track();
         spawn_local(async move {
             if let Ok(fetched_card) = get_card(id()).await {
                 set_card.set(Some(fetched_card));
@@ -37,7 +42,29 @@ pub fn ViewCard() -> impl IntoView {
         });
     });
 
+    let tab_class = move |active: bool| {
+        if active {
+            "px-4 py-2 border-b-2 border-blue-500 text-blue-700"
+        } else {
+            "px-4 py-2 text-slate-500 hover:text-slate-700"
+        }
+    };
+
     view! {
+        <div class="max-w-[600px] mx-auto mt-8 px-4 flex gap-2 border-b border-slate-200">
+            <button
+                class=move || tab_class(!show_history.get())
+                on:click=move |_| show_history.set(false)
+            >
+                "Card"
+            </button>
+            <button
+                class=move || tab_class(show_history.get())
+                on:click=move |_| show_history.set(true)
+            >
+                "History"
+            </button>
+        </div>
         <Show
             when=move || card.get().is_some()
             fallback=|| {
@@ -50,7 +77,21 @@ pub fn ViewCard() -> impl IntoView {
         >
             {move || {
                 let card = card.get().unwrap();
-                view! { <Flashcard card=card on_answer=Callback::new(move |_| {}) /> }
+                if show_history.get() {
+                    view! {
+                        <CardHistory
+                            card=card
+                            on_restore=move || {
+                                show_history.set(false);
+                                reload.update(|r| *r += 1);
+                            }
+                        />
+                    }
+                        .into_any()
+                } else {
+                    view! { <Flashcard card=card on_answer=Callback::new(move |_| {}) /> }
+                        .into_any()
+                }
             }}
         </Show>
     }
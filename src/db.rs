@@ -5,13 +5,13 @@
 use crate::settings::Settings;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use duckdb::types::Value;
+use duckdb::types::{TimeUnit, Value};
 use duckdb::{params, Connection, Error as DuckdbError};
 use once_cell::sync::OnceCell;
 use std::error::Error;
 use std::sync::Mutex;
 
-use crate::model::{Flashcard, ReviewHistory};
+use crate::model::{CardRevision, Flashcard, Grade, ReviewHistory};
 
 /// NOTES:
 /// * duckdb-rs doesn't support arrays, so tags are stored in a separate table.
@@ -44,6 +44,21 @@ static INIT_TABLES_SQL: &str = "
         PRIMARY KEY (flashcard_id, review_date),
         FOREIGN KEY (flashcard_id) REFERENCES flashcards(id),
     );
+    -- Added later, so created for existing databases too.
+    ALTER TABLE review_history ADD COLUMN IF NOT EXISTS grade TEXT;
+    -- Explanation of the grade, when the answer was graded by an LLM.
+    ALTER TABLE review_history ADD COLUMN IF NOT EXISTS feedback TEXT;
+
+    CREATE SEQUENCE IF NOT EXISTS seq_flashcard_revisions;
+    CREATE TABLE IF NOT EXISTS flashcard_revisions (
+        id INTEGER PRIMARY KEY DEFAULT NEXTVAL('seq_flashcard_revisions'),
+        flashcard_id INTEGER,
+        question TEXT,
+        answer TEXT,
+        examples TEXT,
+        revised_at TIMESTAMP,
+        FOREIGN KEY (flashcard_id) REFERENCES flashcards(id),
+    );
 ";
 
 static DATABASE: OnceCell<Mutex<Database>> = OnceCell::new();
@@ -74,7 +89,8 @@ impl Database {
         Ok(Self { conn })
     }
 
-    pub fn add_card(&self, card: &Flashcard) -> Result<(), anyhow::Error> {
+    /// Returns the id of the new card.
+    pub fn add_card(&self, card: &Flashcard) -> Result<i64, anyhow::Error> {
         self.conn.execute("BEGIN TRANSACTION", params![])?;
 
         let mut stmt = self.conn.prepare(
@@ -103,6 +119,29 @@ impl Database {
         }
 
         self.conn.execute("COMMIT", params![])?;
+        Ok(flashcard_id)
+    }
+
+    pub fn delete_card(&self, id: i64) -> Result<(), anyhow::Error> {
+        self.conn.execute("BEGIN TRANSACTION", params![])?;
+        self.delete_card_references(id)?;
+        self.conn.execute("COMMIT", params![])?;
+        self.conn
+            .execute("DELETE FROM flashcards WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    /// Deletes the rows referencing the card. Doesn't start a transaction.
+    /// NOTE: the card itself can only be deleted once this is committed: like in `update_card`,
+    /// duckdb doesn't see the rows deleted in the transaction and the foreign keys fail:
+    ///     Constraint Error: Violates foreign key constraint because key "id: 1" is still referenced
+    fn delete_card_references(&self, id: i64) -> Result<(), DuckdbError> {
+        for table in ["flashcard_tags", "review_history", "flashcard_revisions"] {
+            self.conn.execute(
+                &format!("DELETE FROM {} WHERE flashcard_id = ?", table),
+                params![id],
+            )?;
+        }
         Ok(())
     }
 
@@ -120,6 +159,15 @@ impl Database {
         Ok(rows.collect::<Result<Vec<_>, _>>()?)
     }
 
+    /// All tags with the number of cards having them, sorted by tag.
+    pub fn tags(&self) -> Result<Vec<(String, usize)>, DuckdbError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT tag, count(*) FROM flashcard_tags GROUP BY tag ORDER BY tag")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)))?;
+        rows.collect()
+    }
+
     pub fn cards_to_review(&self) -> Result<Vec<Flashcard>, anyhow::Error> {
         let mut stmt = self.conn.prepare(
             "SELECT f.*, group_concat(ft.tag) from flashcards f 
@@ -131,19 +179,63 @@ impl Database {
     }
 
     pub fn ok(&self, card_id: i64) -> Result<(), Box<dyn Error>> {
+        self.review(card_id, Grade::Good)
+    }
+
+    pub fn fail(&self, card_id: i64) -> Result<(), Box<dyn Error>> {
+        self.review(card_id, Grade::Again)
+    }
+
+    /// Reschedules the card according to the grade and records the review.
+    /// Both the web app and the CLI review through this.
+    pub fn review(&self, card_id: i64, grade: Grade) -> Result<(), Box<dyn Error>> {
+        self.review_with_feedback(card_id, grade, None)
+    }
+
+    /// Same as `review()`, but also keeps the explanation of the grade.
+    pub fn review_with_feedback(
+        &self,
+        card_id: i64,
+        grade: Grade,
+        feedback: Option<&str>,
+    ) -> Result<(), Box<dyn Error>> {
+        let review_after_secs: i64 = self.conn.query_row(
+            "SELECT review_after_secs FROM flashcards WHERE id = ?",
+            params![card_id],
+            |row| row.get(0),
+        )?;
+
         self.conn.execute("BEGIN TRANSACTION", params![])?;
-        self.conn.execute("UPDATE flashcards SET last_reviewed = CURRENT_TIMESTAMP, review_after_secs = review_after_secs * 2 WHERE id = ?", params![card_id])?;
-        self.conn.execute("INSERT INTO review_history (flashcard_id, review_date, remembered) VALUES (?, CURRENT_TIMESTAMP, TRUE)", params![card_id])?;
+        self.conn.execute(
+            "UPDATE flashcards SET last_reviewed = CURRENT_TIMESTAMP, review_after_secs = ? WHERE id = ?",
+            params![grade.next_review_after_secs(review_after_secs), card_id],
+        )?;
+        self.conn.execute(
+            "INSERT INTO review_history (flashcard_id, review_date, remembered, grade, feedback)
+             VALUES (?, CURRENT_TIMESTAMP, ?, ?, ?)",
+            params![card_id, grade.remembered(), grade.as_str(), feedback],
+        )?;
         self.conn.execute("COMMIT", params![])?;
         Ok(())
     }
 
-    pub fn fail(&self, card_id: i64) -> Result<(), Box<dyn Error>> {
+    /// Reverts the latest review of the card: restores the schedule the card had before it.
+    pub fn undo_review(&self, card_before_review: &Flashcard) -> Result<(), Box<dyn Error>> {
+        let card_id = card_before_review.id;
         self.conn.execute("BEGIN TRANSACTION", params![])?;
-        // Don't prompt to review immediately.
-        // Review no earlier than after 6 hours.
-        self.conn.execute("UPDATE flashcards SET last_reviewed = CURRENT_TIMESTAMP, review_after_secs = 21600 WHERE id = ?", params![card_id])?;
-        self.conn.execute("INSERT INTO review_history (flashcard_id, review_date, remembered) VALUES (?, CURRENT_TIMESTAMP, FALSE)", params![card_id])?;
+        self.conn.execute(
+            "UPDATE flashcards SET last_reviewed = ?, review_after_secs = ? WHERE id = ?",
+            params![
+                to_duckdb_timestamp(card_before_review.last_reviewed),
+                card_before_review.review_after_secs,
+                card_id,
+            ],
+        )?;
+        self.conn.execute(
+            "DELETE FROM review_history WHERE flashcard_id = ?
+             AND review_date = (SELECT max(review_date) FROM review_history WHERE flashcard_id = ?)",
+            params![card_id, card_id],
+        )?;
         self.conn.execute("COMMIT", params![])?;
         Ok(())
     }
@@ -165,6 +257,14 @@ impl Database {
         //     Some("Constraint Error: Duplicate key \"flashcard_id: 1, tag: tag1\" violates primary key constraint.
         //self.conn.execute("BEGIN TRANSACTION", params![])?;
 
+        // Keep the previous version of the content, if it changes.
+        self.conn.execute(
+            "INSERT INTO flashcard_revisions (flashcard_id, question, answer, examples, revised_at)
+             SELECT id, question, answer, examples, CURRENT_TIMESTAMP FROM flashcards
+             WHERE id = ? AND (question IS DISTINCT FROM ? OR answer IS DISTINCT FROM ? OR examples IS DISTINCT FROM ?)",
+            params![card.id, card.question, card.answer, card.examples],
+        )?;
+
         // Update the flashcard
         self.conn.execute(
             "UPDATE flashcards SET question = ?, answer = ?, examples = ?, source = ?, img = ?, question_img = ? WHERE id = ?",
@@ -197,13 +297,85 @@ impl Database {
         Ok(())
     }
 
+    /// Merges one card into another: the merged card's tags and review history are moved to
+    /// the kept card and the merged card is deleted. Its revisions are deleted with it: they are
+    /// versions of another card, so they don't belong to the kept card's history.
+    pub fn merge_cards(&self, keep_id: i64, merge_id: i64) -> Result<(), Box<dyn Error>> {
+        self.conn.execute("BEGIN TRANSACTION", params![])?;
+        self.conn.execute(
+            "INSERT INTO flashcard_tags (flashcard_id, tag)
+             SELECT ?, tag FROM flashcard_tags WHERE flashcard_id = ? ON CONFLICT DO NOTHING",
+            params![keep_id, merge_id],
+        )?;
+        self.conn.execute(
+            "INSERT INTO review_history (flashcard_id, review_date, remembered, grade, feedback)
+             SELECT ?, review_date, remembered, grade, feedback FROM review_history
+             WHERE flashcard_id = ? ON CONFLICT DO NOTHING",
+            params![keep_id, merge_id],
+        )?;
+
+        self.delete_card_references(merge_id)?;
+        self.conn.execute("COMMIT", params![])?;
+        self.conn
+            .execute("DELETE FROM flashcards WHERE id = ?", params![merge_id])?;
+        Ok(())
+    }
+
+    /// Previous versions of the card, the most recent first.
+    pub fn card_revisions(&self, card_id: i64) -> Result<Vec<CardRevision>, DuckdbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, flashcard_id, question, answer, examples, revised_at FROM flashcard_revisions
+            WHERE flashcard_id = ? ORDER BY revised_at DESC, id DESC",
+        )?;
+        let rows = stmt.query_map([card_id], |row| {
+            Ok(CardRevision {
+                id: row.get(0)?,
+                flashcard_id: row.get(1)?,
+                question: row.get(2)?,
+                answer: row.get(3)?,
+                examples: row.get(4)?,
+                revised_at: from_duckdb_timestamp(row.get::<_, Value>(5)?),
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Brings back the content of a previous version.
+    /// The replaced content becomes a revision itself, so restoring can be undone.
+    pub fn restore_revision(&self, revision_id: i64) -> Result<(), Box<dyn Error>> {
+        let (card_id, question, answer, examples) = self.conn.query_row(
+            "SELECT flashcard_id, question, answer, examples FROM flashcard_revisions WHERE id = ?",
+            [revision_id],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                ))
+            },
+        )?;
+
+        let mut card = self.get_card(card_id)?;
+        card.question = question;
+        card.answer = answer;
+        card.examples = examples;
+        self.update_card(&card)
+    }
+
     pub fn review_history(&self) -> Result<Vec<ReviewHistory>, anyhow::Error> {
-        let mut stmt = self.conn.prepare("SELECT * FROM review_history")?;
+        let mut stmt = self.conn.prepare(
+            "SELECT flashcard_id, review_date, remembered, grade, feedback FROM review_history",
+        )?;
         let rows = stmt.query_map([], |row| {
             Ok(ReviewHistory {
                 flashcard_id: row.get::<_, i64>(0)?,
                 review_date: from_duckdb_timestamp(row.get::<_, Value>(1)?),
                 remembered: row.get::<_, bool>(2)?,
+                grade: row
+                    .get::<_, Option<String>>(3)?
+                    .and_then(|grade| grade.parse().ok()),
+                feedback: row.get(4)?,
             })
         })?;
         Ok(rows.collect::<Result<Vec<_>, _>>()?)
@@ -257,6 +429,10 @@ pub fn from_duckdb_timestamp(t: Value) -> DateTime<Utc> {
     }
 }
 
+pub fn to_duckdb_timestamp(t: DateTime<Utc>) -> Value {
+    Value::Timestamp(TimeUnit::Microsecond, t.timestamp_micros())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -293,4 +469,115 @@ mod tests {
         );
         assert_eq!(review_history[0].remembered, true);
     }
+
+    #[test]
+    fn test_undo_review_restores_schedule() {
+        let db = Database::in_memory().unwrap();
+        let mut card = Flashcard::new("question1".to_string(), "answer1".to_string());
+        card.tags = vec!["tag1".to_string()];
+        db.add_card(&card).unwrap();
+        let card = db.get_card(1).unwrap();
+
+        db.review(1, Grade::Easy).unwrap();
+        assert_eq!(
+            db.get_card(1).unwrap().review_after_secs,
+            card.review_after_secs * 3
+        );
+
+        db.undo_review(&card).unwrap();
+        let undone = db.get_card(1).unwrap();
+        assert_eq!(undone.last_reviewed, card.last_reviewed);
+        assert_eq!(undone.review_after_secs, card.review_after_secs);
+        assert!(db.review_history().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_review_with_feedback_is_kept_in_history() {
+        let db = Database::in_memory().unwrap();
+        let card = Flashcard::new("question1".to_string(), "answer1".to_string());
+        db.add_card(&card).unwrap();
+
+        db.review_with_feedback(1, Grade::Hard, Some("Missed the second part"))
+            .unwrap();
+
+        let review_history = db.review_history().unwrap();
+        assert_eq!(review_history[0].grade, Some(Grade::Hard));
+        assert_eq!(
+            review_history[0].feedback.as_deref(),
+            Some("Missed the second part")
+        );
+        assert!(review_history[0].remembered);
+    }
+
+    #[test]
+    fn test_update_card_stores_revisions_that_can_be_restored() {
+        let db = Database::in_memory().unwrap();
+        let mut card = Flashcard::new("question1".to_string(), "answer1".to_string());
+        card.tags = vec!["tag1".to_string()];
+        db.add_card(&card).unwrap();
+
+        card.id = 1;
+        db.update_card(&card).unwrap();
+        assert!(db.card_revisions(1).unwrap().is_empty());
+
+        card.answer = "answer2".to_string();
+        db.update_card(&card).unwrap();
+        let revisions = db.card_revisions(1).unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].answer, "answer1");
+
+        db.restore_revision(revisions[0].id).unwrap();
+        assert_eq!(db.get_card(1).unwrap().answer, "answer1");
+        let revisions = db.card_revisions(1).unwrap();
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[0].answer, "answer2");
+    }
+
+    #[test]
+    fn test_merge_cards_moves_tags_and_review_history() {
+        let db = Database::in_memory().unwrap();
+        let mut card = Flashcard::new("What is a monad?".to_string(), "answer1".to_string());
+        card.tags = vec!["haskell".to_string()];
+        db.add_card(&card).unwrap();
+        card.question = "What's a monad?".to_string();
+        card.tags = vec!["fp".to_string(), "haskell".to_string()];
+        db.add_card(&card).unwrap();
+        db.ok(2).unwrap();
+        card.id = 2;
+        card.answer = "answer2".to_string();
+        db.update_card(&card).unwrap();
+
+        db.merge_cards(1, 2).unwrap();
+
+        let cards = db.all_cards(None).unwrap();
+        assert_eq!(cards.len(), 1);
+        let mut tags = cards[0].tags.clone();
+        tags.sort();
+        assert_eq!(tags, vec!["fp".to_string(), "haskell".to_string()]);
+        let review_history = db.review_history().unwrap();
+        assert_eq!(review_history.len(), 1);
+        assert_eq!(review_history[0].flashcard_id, 1);
+        assert!(db.card_revisions(1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_delete_card_removes_its_tags_and_history() {
+        let db = Database::in_memory().unwrap();
+        let mut card = Flashcard::new("question1".to_string(), "answer1".to_string());
+        card.tags = vec!["tag1".to_string()];
+        let id = db.add_card(&card).unwrap();
+        db.add_card(&card).unwrap();
+        db.ok(id).unwrap();
+        db.fail(id).unwrap();
+        card.id = id;
+        card.answer = "answer2".to_string();
+        db.update_card(&card).unwrap();
+
+        db.delete_card(id).unwrap();
+
+        assert_eq!(db.all_cards(None).unwrap().len(), 1);
+        assert!(db.review_history().unwrap().is_empty());
+        assert!(db.card_revisions(id).unwrap().is_empty());
+        assert_eq!(db.tags().unwrap(), vec![("tag1".to_string(), 1)]);
+    }
 }
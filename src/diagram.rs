@@ -0,0 +1,307 @@
+//! Diagrams in card Markdown rendered as SVG, on the server only.
+//!
+//! DOT is laid out with [layout-rs](https://github.com/nadavrot/layout).
+//! There's no Rust renderer for Mermaid, so the flowchart subset of it is translated to DOT:
+//! ```mermaid
+//! graph LR
+//!     A[Start] --> B{Done?}
+//!     B -->|Yes| C((End))
+//!     B -- No --> A
+//! ```
+
+#![cfg(feature = "ssr")]
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::panic;
+use std::sync::{Mutex, OnceLock};
+
+use layout::backends::svg::SVGWriter;
+use layout::gv::{DotParser, GraphBuilder};
+
+use crate::components::DiagramKind;
+
+/// Rendered SVGs keyed by the hash of the diagram source.
+/// Cards are rendered over and over again during reviews, while the layout is not cheap.
+static SVG_CACHE: OnceLock<Mutex<SvgCache>> = OnceLock::new();
+
+/// Diagrams kept in [SVG_CACHE]. The live preview of the editor renders every edit,
+/// so the oldest ones are dropped.
+const SVG_CACHE_CAPACITY: usize = 100;
+
+#[derive(Default)]
+struct SvgCache {
+    svgs: HashMap<u64, String>,
+    /// Keys from the oldest to the newest.
+    order: VecDeque<u64>,
+}
+
+impl SvgCache {
+    fn get(&self, key: u64) -> Option<&String> {
+        self.svgs.get(&key)
+    }
+
+    fn insert(&mut self, key: u64, svg: String) {
+        if self.svgs.insert(key, svg).is_some() {
+            return;
+        }
+        self.order.push_back(key);
+        if self.order.len() > SVG_CACHE_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.svgs.remove(&oldest);
+            }
+        }
+    }
+}
+
+pub fn render_svg(kind: DiagramKind, source: &str) -> Result<String, String> {
+    let key = content_hash(kind, source);
+    let cache = SVG_CACHE.get_or_init(Default::default);
+    if let Some(svg) = cache.lock().map_err(|e| e.to_string())?.get(key) {
+        return Ok(svg.clone());
+    }
+
+    let dot = match kind {
+        DiagramKind::Dot => source.to_string(),
+        DiagramKind::Mermaid => mermaid_to_dot(source)?,
+    };
+    let svg = dot_to_svg(&dot)?;
+
+    cache
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(key, svg.clone());
+    Ok(svg)
+}
+
+fn content_hash(kind: DiagramKind, source: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    kind.hash(&mut hasher);
+    source.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// layout-rs panics on some graphs, e.g. an empty one, so a panic is an error as well.
+fn dot_to_svg(dot: &str) -> Result<String, String> {
+    panic::catch_unwind(|| {
+        let mut parser = DotParser::new(dot);
+        let graph = parser.process()?;
+
+        let mut builder = GraphBuilder::new();
+        builder.visit_graph(&graph);
+        let mut visual_graph = builder.get();
+
+        let mut svg = SVGWriter::new();
+        visual_graph.do_it(false, false, false, &mut svg);
+        Ok(svg.finalize())
+    })
+    .unwrap_or_else(|_| Err("Failed to lay out the graph".to_string()))
+}
+
+/// A node in a Mermaid flowchart statement, e.g. `A[Start]`.
+#[derive(Debug, PartialEq)]
+struct MermaidNode<'a> {
+    id: &'a str,
+    label: Option<&'a str>,
+    shape: Option<&'static str>,
+}
+
+/// Translates a Mermaid flowchart (`graph` or `flowchart`) to DOT.
+fn mermaid_to_dot(source: &str) -> Result<String, String> {
+    let mut lines = source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("%%"));
+
+    let header = lines.next().ok_or("Empty mermaid diagram")?;
+    let mut header_parts = header.split_whitespace();
+    if !matches!(header_parts.next(), Some("graph") | Some("flowchart")) {
+        return Err(format!("Unsupported mermaid diagram: {}", header));
+    }
+
+    let mut dot = String::from("digraph {\n");
+    if let Some("LR") | Some("RL") = header_parts.next().map(|d| d.trim_end_matches(';')) {
+        dot += "  rankdir=LR;\n";
+    }
+
+    let statements = lines
+        .flat_map(|line| line.split(';'))
+        .map(str::trim)
+        .filter(|stmt| !stmt.is_empty());
+    for stmt in statements {
+        let (mut node, mut rest) = parse_mermaid_node(stmt)?;
+        dot += &dot_node(&node);
+        while !rest.is_empty() {
+            let (label, after_arrow) = parse_mermaid_arrow(rest)?;
+            let (next_node, after_node) = parse_mermaid_node(after_arrow)?;
+            dot += &dot_node(&next_node);
+            dot += &format!(
+                "  \"{}\" -> \"{}\"{};\n",
+                node.id,
+                next_node.id,
+                label
+                    .map(|l| format!(" [label=\"{}\"]", escape_dot(l)))
+                    .unwrap_or_default()
+            );
+            node = next_node;
+            rest = after_node;
+        }
+    }
+
+    dot += "}\n";
+    Ok(dot)
+}
+
+fn dot_node(node: &MermaidNode) -> String {
+    let mut attrs = Vec::new();
+    if let Some(label) = node.label {
+        attrs.push(format!("label=\"{}\"", escape_dot(label)));
+    }
+    if let Some(shape) = node.shape {
+        attrs.push(format!("shape={}", shape));
+    }
+    if attrs.is_empty() {
+        format!("  \"{}\";\n", node.id)
+    } else {
+        format!("  \"{}\" [{}];\n", node.id, attrs.join(", "))
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Parses `A`, `A[label]`, `A(label)`, `A((label))` or `A{label}`.
+///
+/// Returns the node and the rest of the statement.
+fn parse_mermaid_node(stmt: &str) -> Result<(MermaidNode<'_>, &str), String> {
+    let stmt = stmt.trim_start();
+    let id_len = stmt
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(stmt.len());
+    if id_len == 0 {
+        return Err(format!("Expected a node id: {}", stmt));
+    }
+    let (id, rest) = stmt.split_at(id_len);
+
+    let shapes = [
+        ("((", "))", Some("circle")),
+        ("[", "]", Some("box")),
+        ("(", ")", None),
+        ("{", "}", None),
+    ];
+    for (open, close, shape) in shapes {
+        if let Some(inner) = rest.strip_prefix(open) {
+            let end = inner
+                .find(close)
+                .ok_or_else(|| format!("Unclosed '{}' in: {}", open, stmt))?;
+            let label = inner[..end].trim().trim_matches('"');
+            let node = MermaidNode {
+                id,
+                label: Some(label),
+                shape,
+            };
+            return Ok((node, inner[end + close.len()..].trim_start()));
+        }
+    }
+
+    let node = MermaidNode {
+        id,
+        label: None,
+        shape: None,
+    };
+    Ok((node, rest.trim_start()))
+}
+
+/// Parses `-->`, `---`, `-.->`, `==>`, `-->|label|` or `-- label -->`.
+///
+/// Returns the optional edge label and the rest of the statement.
+fn parse_mermaid_arrow(stmt: &str) -> Result<(Option<&str>, &str), String> {
+    let arrow_len = stmt
+        .find(|c: char| !matches!(c, '-' | '.' | '=' | '>'))
+        .unwrap_or(stmt.len());
+    if arrow_len < 2 {
+        return Err(format!("Expected an arrow: {}", stmt));
+    }
+    let (arrow, rest) = stmt.split_at(arrow_len);
+
+    // Text in the middle of the arrow: `A -- label --> B`.
+    if arrow == "--" || arrow == "==" {
+        let end = ["-->", "==>", "---"]
+            .iter()
+            .filter_map(|a| rest.find(a).map(|idx| (idx, a.len())))
+            .min()
+            .ok_or_else(|| format!("Unclosed arrow: {}", stmt))?;
+        return Ok((Some(rest[..end.0].trim()), &rest[end.0 + end.1..]));
+    }
+
+    // Text after the arrow: `A -->|label| B`.
+    if let Some(labeled) = rest.trim_start().strip_prefix('|') {
+        let end = labeled
+            .find('|')
+            .ok_or_else(|| format!("Unclosed edge label: {}", stmt))?;
+        return Ok((Some(labeled[..end].trim()), &labeled[end + 1..]));
+    }
+
+    Ok((None, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mermaid_to_dot() {
+        let dot = mermaid_to_dot(
+            "graph LR\n  A[Start] --> B{Done?}\n  B -->|Yes| C((End))\n  B -- No --> A",
+        )
+        .unwrap();
+        assert_eq!(
+            dot,
+            "digraph {
+  rankdir=LR;
+  \"A\" [label=\"Start\", shape=box];
+  \"B\" [label=\"Done?\"];
+  \"A\" -> \"B\";
+  \"B\";
+  \"C\" [label=\"End\", shape=circle];
+  \"B\" -> \"C\" [label=\"Yes\"];
+  \"B\";
+  \"A\";
+  \"B\" -> \"A\" [label=\"No\"];
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_mermaid_to_dot_rejects_unsupported_diagrams() {
+        assert!(mermaid_to_dot("sequenceDiagram\n  Alice->>Bob: Hi").is_err());
+        assert!(mermaid_to_dot("").is_err());
+    }
+
+    #[test]
+    fn test_render_svg() {
+        assert!(render_svg(DiagramKind::Dot, "digraph { a -> b }")
+            .unwrap()
+            .contains("<svg"));
+        assert!(render_svg(DiagramKind::Dot, "digraph { a -> }").is_err());
+        // Panics in layout-rs.
+        assert!(render_svg(DiagramKind::Dot, "digraph {}").is_err());
+        assert!(render_svg(DiagramKind::Mermaid, "graph LR").is_err());
+    }
+
+    #[test]
+    fn test_svg_cache_is_bounded() {
+        let mut cache = SvgCache::default();
+        for key in 0..SVG_CACHE_CAPACITY as u64 + 10 {
+            cache.insert(key, key.to_string());
+        }
+        cache.insert(20, "20".to_string());
+        assert_eq!(cache.svgs.len(), SVG_CACHE_CAPACITY);
+        assert_eq!(cache.order.len(), SVG_CACHE_CAPACITY);
+        assert_eq!(cache.get(9), None);
+        assert_eq!(cache.get(10).unwrap(), "10");
+    }
+}
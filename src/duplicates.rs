@@ -0,0 +1,141 @@
+//! Duplicate and near-duplicate card detection.
+//!
+//! Questions are normalized and compared by the trigram similarity, which tolerates
+//! slightly different wording, punctuation and typos.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::model::{DuplicatePair, Flashcard};
+
+/// Questions at least this similar are reported as duplicates.
+pub const SIMILARITY_THRESHOLD: f64 = 0.6;
+
+/// Lowercase alphanumeric words separated by a single space.
+pub fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Character trigrams of the normalized text, each word padded with spaces.
+fn trigrams(text: &str) -> HashSet<String> {
+    normalize(text)
+        .split(' ')
+        .filter(|word| !word.is_empty())
+        .flat_map(|word| {
+            let chars: Vec<char> = format!("  {} ", word).chars().collect();
+            chars
+                .windows(3)
+                .map(|w| w.iter().collect::<String>())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Jaccard similarity of the trigram sets: 1.0 means the same normalized text.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    jaccard(&trigrams(a), &trigrams(b))
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let shared = a.intersection(b).count();
+    shared as f64 / (a.len() + b.len() - shared) as f64
+}
+
+/// Cards with questions similar to the given one, the most similar first.
+pub fn find_similar(question: &str, cards: &[Flashcard], threshold: f64) -> Vec<Flashcard> {
+    let question = trigrams(question);
+    let mut similar = cards
+        .iter()
+        .map(|card| (jaccard(&question, &trigrams(&card.question)), card))
+        .filter(|(score, _)| *score >= threshold)
+        .collect::<Vec<_>>();
+    similar.sort_by(|a, b| b.0.total_cmp(&a.0));
+    similar.into_iter().map(|(_, card)| card.clone()).collect()
+}
+
+/// All pairs of cards with similar questions, the most similar first.
+pub fn find_duplicates(cards: &[Flashcard], threshold: f64) -> Vec<DuplicatePair> {
+    let card_trigrams: Vec<HashSet<String>> =
+        cards.iter().map(|card| trigrams(&card.question)).collect();
+
+    // Only compare the cards that share at least one trigram.
+    let mut index: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, trigrams) in card_trigrams.iter().enumerate() {
+        for trigram in trigrams {
+            index.entry(trigram.as_str()).or_default().push(i);
+        }
+    }
+
+    let mut pairs = Vec::new();
+    for (i, trigrams) in card_trigrams.iter().enumerate() {
+        let mut shared: HashMap<usize, usize> = HashMap::new();
+        for trigram in trigrams {
+            for &j in index[trigram.as_str()].iter().filter(|&&j| j > i) {
+                *shared.entry(j).or_default() += 1;
+            }
+        }
+        for (j, shared) in shared {
+            let union = trigrams.len() + card_trigrams[j].len() - shared;
+            let similarity = shared as f64 / union as f64;
+            if similarity >= threshold {
+                pairs.push(DuplicatePair {
+                    card: cards[i].clone(),
+                    duplicate: cards[j].clone(),
+                    similarity,
+                });
+            }
+        }
+    }
+
+    pairs.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(id: i64, question: &str) -> Flashcard {
+        let mut card = Flashcard::new(question.to_string(), "answer".to_string());
+        card.id = id;
+        card
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("  What is  a *monad*?\n"),
+            "what is a monad".to_string()
+        );
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("What is a monad?", "what is a MONAD"), 1.0);
+        assert!(similarity("What is a monad?", "What's a monad?") > SIMILARITY_THRESHOLD);
+        assert!(similarity("What is a monad?", "How does TCP work?") < SIMILARITY_THRESHOLD);
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let cards = vec![
+            card(1, "What is a monad?"),
+            card(2, "How does TCP slow start work?"),
+            card(3, "What's a monad?"),
+            card(4, "How does TCP slow-start work"),
+        ];
+
+        let pairs = find_duplicates(&cards, SIMILARITY_THRESHOLD);
+        let ids: Vec<(i64, i64)> = pairs
+            .iter()
+            .map(|pair| (pair.card.id, pair.duplicate.id))
+            .collect();
+        assert_eq!(ids, vec![(2, 4), (1, 3)]);
+    }
+}
@@ -16,6 +16,12 @@ pub enum AppError {
     ServerFnError(ServerFnErrorErr),
     LlmError(String),
     GoogleTranslateError(String),
+    UnsupportedLanguage(String),
+    InvalidFile(String),
+    /// No translator knows the text.
+    NoTranslation(String),
+    /// A request that doesn't make sense, e.g. a tense the language doesn't have.
+    InvalidInput(String),
 }
 
 impl fmt::Display for AppError {
@@ -25,10 +31,22 @@ impl fmt::Display for AppError {
             AppError::ServerFnError(e) => write!(f, "{}", e),
             AppError::LlmError(e) => write!(f, "{}", e),
             AppError::GoogleTranslateError(e) => write!(f, "{}", e),
+            AppError::UnsupportedLanguage(lang) => write!(
+                f,
+                "Unsupported language: {}. Add it to `languages` in settings.toml.",
+                lang
+            ),
+            AppError::InvalidFile(e) => write!(f, "Invalid file: {}", e),
+            AppError::NoTranslation(text) => {
+                write!(f, "No translation for \"{}\", type it in", text)
+            }
+            AppError::InvalidInput(e) => write!(f, "Invalid input: {}", e),
         }
     }
 }
 
+impl std::error::Error for AppError {}
+
 #[cfg(feature = "ssr")]
 impl From<DuckdbError> for AppError {
     fn from(e: DuckdbError) -> Self {
@@ -43,6 +61,13 @@ impl From<PromptError> for AppError {
     }
 }
 
+#[cfg(feature = "ssr")]
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        AppError::LlmError(e.to_string())
+    }
+}
+
 #[cfg(feature = "ssr")]
 impl From<translators::Error> for AppError {
     fn from(e: translators::Error) -> Self {
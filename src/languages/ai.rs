@@ -1,13 +1,18 @@
 #![cfg(feature = "ssr")]
 
 use duckdb::Error as DuckdbError;
-use rig::client::CompletionClient;
-use rig::{client::ProviderClient, completion::Prompt, providers::anthropic};
 
-use super::model::NewSentence;
+use super::conjugation::validate;
+use super::model::{
+    familiar_words, is_conjugation_drill, Conjugation, Familiarity, GeneratedStory, NewSentence,
+    Word,
+};
 use crate::db::Database as FlashcardsDb;
 use crate::errors::AppError;
-use crate::settings::{Language, Settings};
+use crate::llm::{
+    parse_xml_tag, prompt_parsed, require_xml_tag, LlmProvider, LlmResponse, Provider,
+};
+use crate::settings::{Language, LanguageInfo, Settings, StorySettings};
 use crate::words_db;
 
 static GEN_NEW_WORDS_PROMPT: &str = "
@@ -18,8 +23,8 @@ Help me to gradually learn the {lang} language. Here is the words I already know
 {dict}
 </dictionary>
 
-Generate a sentence that uses the words from my dictionary but introduces one new word. 
-This new word cannot be an article like 'el', 'la', 'los', 'las', 'un', 'una', etc.
+Generate a sentence that uses the words from my dictionary but introduces one new word. {new_word}
+This new word cannot be an article or a common word like {skipped_words}.
 Keep the sentence short. 
 Output in this format:
 
@@ -43,7 +48,7 @@ Here is the {lang} sentences I already know.
 {sentences}
 </sentences>
 
-List all the words in there. Omit articles like 'a', 'the', etc.
+List all the words in there. Omit articles and common words like {skipped_words}.
 Omit places, names, etc.
 Use such format:
 
@@ -64,198 +69,429 @@ Here is the words I already know in {lang}:
 </dictionary>
 
 Generate a short story using these words. You don't need to use all the words.
+{practice}
 Ouput only the story, no other text.
 ";
 
+static SIMPLIFY_STORY_PROMPT: &str = "
+Here is the words I already know in {lang}:
+
+<dictionary>
+{dict}
+</dictionary>
+
+Here is a story in {lang}:
+
+<story>
+{story}
+</story>
+
+It's too hard for me, I don't know these words:
+
+<unknown>
+{unknown}
+</unknown>
+
+Rewrite the story in simpler {lang}: replace the words I don't know with the words from my dictionary.
+Keep it short. Ouput only the story, no other text.
+";
+
+static CONJUGATE_PROMPT: &str = "
+Conjugate the {lang} verb '{verb}' in the tense '{tense}'.
+Write only the verb forms, without the pronouns, for each of these persons:
+
+<conjugation>
+{persons}
+</conjugation>
+";
+
 // AI agent that understands the language we are learning.
 pub struct Agent {
-    llm_client: rig::agent::Agent<anthropic::completion::CompletionModel>,
+    llm: Provider,
     /// The language that this AI agent is working with.
     pub lang: Language,
 }
 
 impl Agent {
     pub fn from_settings() -> Self {
-        let settings = Settings::get();
-        Self::new(settings.learning_language, &settings.anthropic_api_key)
+        Self::for_language(Settings::get().learning_language.clone())
     }
-    pub fn new(lang: Language, anthropic_api_key: &str) -> Self {
-        Self {
-            llm_client: anthropic::Client::new(anthropic_api_key)
-                .agent(anthropic::CLAUDE_4_SONNET)
-                .max_tokens(1000)
-                .build(),
-            lang: lang,
-        }
+
+    /// Agent for the given language, using the LLM from the settings.
+    pub fn for_language(lang: Language) -> Self {
+        Self::new(lang, Provider::from_settings())
+    }
+
+    pub fn new(lang: Language, llm: Provider) -> Self {
+        Self { llm, lang }
     }
 
     pub async fn gen_new_sentence(&self) -> Result<NewSentence, AppError> {
-        let words = words_db!().all_words()?;
+        let info = self.lang.info()?;
+        let words = words_db!(self.lang).all_words()?;
+        let new_word = match next_new_word(&words) {
+            Some(word) => format!("The new word must be '{}'.", word.word),
+            None => "".to_string(),
+        };
         let prompt = GEN_NEW_WORDS_PROMPT
             .replace("{lang}", self.lang.as_str())
-            .replace(
-                "{dict}",
-                &words
-                    .iter()
-                    .map(|word| format!("{}", word.word))
-                    .collect::<Vec<String>>()
-                    .join("\n"),
-            );
+            .replace("{skipped_words}", &skipped_words(&info))
+            .replace("{new_word}", &new_word)
+            .replace("{dict}", &word_list(familiar_words(&words)));
 
-        let response = self.llm_client.prompt(prompt).await?;
-
-        let text = parse_xml_tag(&response, "new_sentence").unwrap();
-        let new_word = parse_xml_tag(&response, "new_word").unwrap();
-        let translation = parse_xml_tag(&response, "translation").unwrap();
-        Ok(NewSentence {
-            text,
-            new_word,
-            translation,
-        })
+        prompt_parsed(&self.llm, &prompt).await
     }
 
     // From flashcards...
     pub async fn populate_words_db(&self) -> Result<(), AppError> {
-        let sentences = get_all_sentences(self.lang.as_str())?;
+        self.populate_words_db_from_tag(self.lang.as_str()).await
+    }
+
+    /// Adds the words used in the answers of flashcards with the given tag.
+    pub async fn populate_words_db_from_tag(&self, tag: &str) -> Result<(), AppError> {
+        let info = self.lang.info()?;
+        let sentences = get_all_sentences(tag)?;
         let prompt = EXTRACT_WORDS_PROMPT
             .replace("{lang}", self.lang.as_str())
+            .replace("{skipped_words}", &skipped_words(&info))
             .replace("{sentences}", &sentences);
 
-        let response = self.llm_client.prompt(&prompt).await?;
-        let words = llm_resp_parse_words(&response);
+        let Words(words) = prompt_parsed(&self.llm, &prompt).await?;
 
-        let words_db = words_db!();
-        for word in words {
-            words_db.add_word(&word, "")?;
+        let words_db = words_db!(self.lang);
+        // The LLM doesn't always follow the instructions.
+        for word in words.iter().filter(|word| !info.is_skipped_word(word)) {
+            words_db.add_word(word, "")?;
         }
 
         Ok(())
     }
 
-    pub async fn gen_story(&self) -> Result<String, AppError> {
-        let words = { words_db!().all_words()? };
+    /// Conjugation table of the verb, checked with `validate()`.
+    pub async fn conjugate(&self, verb: &str, tense: &str) -> Result<Conjugation, AppError> {
+        let info = self.lang.info()?;
+        let persons = info
+            .persons
+            .iter()
+            .map(|person| format!("{}: ...", person))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let prompt = CONJUGATE_PROMPT
+            .replace("{lang}", self.lang.as_str())
+            .replace("{verb}", verb)
+            .replace("{tense}", tense)
+            .replace("{persons}", &persons);
+
+        let mut attempt = 0;
+        loop {
+            let ConjugationForms(forms) = prompt_parsed(&self.llm, &prompt).await?;
+            let conjugation = Conjugation {
+                verb: verb.to_string(),
+                tense: tense.to_string(),
+                forms,
+            };
+            match validate(&conjugation, &info) {
+                Ok(()) => return Ok(conjugation),
+                // A wrong table would teach wrong forms.
+                Err(e) if attempt >= 1 => return Err(AppError::LlmError(e)),
+                Err(e) => {
+                    log::warn!("Invalid conjugation of '{}', retrying: {}", verb, e);
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Writes a story with my words. While I know less than `min_coverage` of its words,
+    /// asks to simplify it, up to `max_attempts` times. Returns the easiest version.
+    pub async fn gen_story(&self, settings: &StorySettings) -> Result<GeneratedStory, AppError> {
+        let info = self.lang.info()?;
+        let words = { words_db!(self.lang).all_words()? };
+        let learning = words
+            .iter()
+            .filter(|word| word.familiarity == Familiarity::Learning)
+            .collect::<Vec<_>>();
+        let practice = if learning.is_empty() {
+            "".to_string()
+        } else {
+            format!(
+                "Use these words I'm still learning a few times:\n\n<learning>\n{}\n</learning>\n",
+                word_list(learning)
+            )
+        };
+        let dict = word_list(familiar_words(&words));
         let prompt = GEN_STORY_PROMPT
             .replace("{lang}", self.lang.as_str())
-            .replace(
-                "{dict}",
-                &words
-                    .iter()
-                    .map(|word| format!("{}", word.word))
-                    .collect::<Vec<String>>()
-                    .join("\n"),
+            .replace("{practice}", &practice)
+            .replace("{dict}", &dict);
+
+        let mut story = self.with_coverage(self.llm.prompt(&prompt).await?, &info)?;
+        for _ in 1..settings.max_attempts {
+            if story.coverage.known_ratio >= settings.min_coverage {
+                break;
+            }
+            log::info!(
+                "Simplifying the story, known words: {:.0}%",
+                story.coverage.known_ratio * 100.0
             );
+            let prompt = SIMPLIFY_STORY_PROMPT
+                .replace("{lang}", self.lang.as_str())
+                .replace("{dict}", &dict)
+                .replace("{story}", &story.text)
+                .replace("{unknown}", &story.coverage.unknown_words.join("\n"));
+            let simpler = self.with_coverage(self.llm.prompt(&prompt).await?, &info)?;
+            if simpler.coverage.known_ratio > story.coverage.known_ratio {
+                story = simpler;
+            }
+        }
+        Ok(story)
+    }
 
-        let response = self.llm_client.prompt(&prompt).await?;
-        Ok(response)
+    fn with_coverage(&self, text: String, info: &LanguageInfo) -> Result<GeneratedStory, AppError> {
+        let text = text.trim().to_string();
+        let coverage = words_db!(self.lang).coverage(&text, info)?;
+        Ok(GeneratedStory { text, coverage })
     }
 }
 
-// From flashcards...
-pub async fn populate_words_db(lang: &str) -> Result<(), AppError> {
-    let sentences = get_all_sentences(lang)?;
-    let prompt = EXTRACT_WORDS_PROMPT
-        .replace("{lang}", lang)
-        .replace("{sentences}", &sentences);
-
-    let anthropic = anthropic::Client::from_env();
-    let agent = anthropic
-        .agent(anthropic::CLAUDE_3_7_SONNET)
-        .max_tokens(1000)
-        .build();
-    let response = agent.prompt(&prompt).await?;
-    let words = llm_resp_parse_words(&response);
-
-    let words_db = words_db!();
-    for word in words {
-        words_db.add_word(&word, "")?;
+/// The next word to introduce: the oldest one that I saved, but never reviewed.
+/// `None` when I already reviewed all my words, then the LLM picks one.
+fn next_new_word(words: &[Word]) -> Option<&Word> {
+    // Nothing was reviewed yet, so all the words are in the dictionary.
+    if words
+        .iter()
+        .all(|word| word.familiarity == Familiarity::New)
+    {
+        return None;
     }
+    words
+        .iter()
+        .filter(|word| word.familiarity == Familiarity::New)
+        .min_by_key(|word| word.created_at)
+}
 
-    Ok(())
+fn word_list(words: Vec<&Word>) -> String {
+    words
+        .iter()
+        .map(|word| word.word.as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
-fn get_all_sentences(lang: &str) -> Result<String, DuckdbError> {
+fn skipped_words(info: &LanguageInfo) -> String {
+    info.articles
+        .iter()
+        .chain(info.stop_words.iter())
+        .map(|word| format!("'{}'", word))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Answers of the cards with the tag, except conjugation drills: their forms aren't words to learn.
+fn get_all_sentences(tag: &str) -> Result<String, DuckdbError> {
     let flashcards_db = FlashcardsDb::get_instance().unwrap().lock().unwrap();
-    let cards = flashcards_db.all_cards(Some(lang.to_string()))?;
+    let cards = flashcards_db.all_cards(Some(tag.to_string()))?;
     Ok(cards
         .iter()
+        .filter(|card| !is_conjugation_drill(card))
         .map(|card| card.answer.clone())
         .collect::<Vec<String>>()
         .join("\n"))
 }
 
-// Parse words from LLM response.
-// e.g.
-// <words>
-// gustaria
-// cocina
-// </words>
-//
-// Returns:
-// ["gustaria", "cocina"]
-fn llm_resp_parse_words(response: &str) -> Vec<String> {
-    let words = parse_xml_tag(response, "words").unwrap_or_default();
-    words
-        .split('\n')
-        .map(|word| word.trim())
-        .filter(|word| !word.is_empty())
-        .map(|word| word.to_string())
-        .collect()
+impl LlmResponse for NewSentence {
+    const FORMAT: &'static str = "<new_sentence>
+...
+</new_sentence>
+<new_word>
+...
+</new_word>
+<translation>
+...
+</translation>";
+
+    fn parse(response: &str) -> Result<Self, AppError> {
+        Ok(NewSentence {
+            text: require_xml_tag(response, "new_sentence")?,
+            new_word: require_xml_tag(response, "new_word")?,
+            translation: require_xml_tag(response, "translation")?,
+        })
+    }
 }
 
-// Minimal XML parser for non-perfect LLM output.
-fn parse_xml_tag(text: &str, tag: &str) -> Option<String> {
-    let start_tag = format!("<{}>", tag);
-    let end_tag = format!("</{}>", tag);
+/// Words listed one per line, e.g.
+/// <words>
+/// gustaria
+/// cocina
+/// </words>
+struct Words(Vec<String>);
 
-    if let Some(start_idx) = text.find(&start_tag) {
-        if let Some(end_idx) = text.find(&end_tag) {
-            let start_content = start_idx + start_tag.len();
-            let content = &text[start_content..end_idx];
-            return Some(content.trim().to_string());
-        }
+impl LlmResponse for Words {
+    const FORMAT: &'static str = "<words>
+gustaria
+cocina
+</words>";
+
+    fn parse(response: &str) -> Result<Self, AppError> {
+        let words = parse_xml_tag(response, "words")
+            .ok_or_else(|| AppError::LlmError("<words> is missing".to_string()))?;
+        Ok(Words(
+            words
+                .lines()
+                // Sometimes words are listed as Markdown bullets.
+                .map(|word| word.trim().trim_start_matches(['-', '*']).trim())
+                .filter(|word| !word.is_empty())
+                .map(|word| word.to_string())
+                .collect(),
+        ))
+    }
+}
+
+/// (person, form) pairs, one per line, e.g.
+/// <conjugation>
+/// yo: hablo
+/// </conjugation>
+struct ConjugationForms(Vec<(String, String)>);
+
+impl LlmResponse for ConjugationForms {
+    const FORMAT: &'static str = "<conjugation>
+yo: hablo
+tú: hablas
+</conjugation>";
+
+    fn parse(response: &str) -> Result<Self, AppError> {
+        let forms = require_xml_tag(response, "conjugation")?
+            .lines()
+            .map(|line| line.trim().trim_start_matches(['-', '*']).trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let (person, form) = line
+                    .split_once(':')
+                    .ok_or_else(|| AppError::LlmError(format!("No person in {:?}", line)))?;
+                Ok((person.trim().to_string(), form.trim().to_string()))
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+        Ok(ConjugationForms(forms))
     }
-    None
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::llm::MockProvider;
+
+    fn parse_words(response: &str) -> Result<Vec<String>, AppError> {
+        Words::parse(response).map(|Words(words)| words)
+    }
+
+    fn word(word: &str, familiarity: Familiarity, days_ago: i64) -> Word {
+        Word {
+            word: word.to_string(),
+            translation: None,
+            created_at: chrono::Utc::now() - chrono::Duration::days(days_ago),
+            forms: Vec::new(),
+            familiarity,
+        }
+    }
 
     #[test]
-    fn test_llm_resp_parse_words() {
+    fn test_words_by_familiarity() {
+        let words = vec![
+            word("casa", Familiarity::Known, 10),
+            word("perro", Familiarity::New, 5),
+            word("gato", Familiarity::New, 7),
+            word("comer", Familiarity::Learning, 3),
+        ];
+        assert_eq!(word_list(familiar_words(&words)), "casa\ncomer");
+        assert_eq!(next_new_word(&words).unwrap().word, "gato");
+
+        // Before any review, all the words are used and the LLM picks the new one.
+        let new_words = vec![word("casa", Familiarity::New, 1)];
+        assert_eq!(word_list(familiar_words(&new_words)), "casa");
+        assert!(next_new_word(&new_words).is_none());
+    }
+
+    #[test]
+    fn test_parse_words() {
         // Test case 1: Normal response with words
         let response1 = "Here is the response:\n<words>\ngustaria\ncocina\n</words>\nThank you!";
-        let result1 = llm_resp_parse_words(response1);
-        assert_eq!(result1, vec!["gustaria", "cocina"]);
+        assert_eq!(parse_words(response1).unwrap(), vec!["gustaria", "cocina"]);
 
         // Test case 2: Response with extra whitespace
         let response2 = "  <words>  \n  gustaria  \n  cocina  \n  </words>  ";
-        let result2 = llm_resp_parse_words(response2);
-        assert_eq!(result2, vec!["gustaria", "cocina"]);
+        assert_eq!(parse_words(response2).unwrap(), vec!["gustaria", "cocina"]);
 
         // Test case 3: Response with empty lines
         let response3 = "<words>\ngustaria\n\ncocina\n\n</words>";
-        let result3 = llm_resp_parse_words(response3);
-        assert_eq!(result3, vec!["gustaria", "cocina"]);
+        assert_eq!(parse_words(response3).unwrap(), vec!["gustaria", "cocina"]);
 
         // Test case 4: Response without tags
-        let response4 = "This response has no tags";
-        let result4 = llm_resp_parse_words(response4);
-        assert_eq!(result4, Vec::<String>::new());
+        assert!(parse_words("This response has no tags").is_err());
 
         // Test case 5: Response with only opening tag
-        let response5 = "<words>\ngustaria\ncocina";
-        let result5 = llm_resp_parse_words(response5);
-        assert_eq!(result5, Vec::<String>::new());
+        assert!(parse_words("<words>\ngustaria\ncocina").is_err());
 
         // Test case 6: Empty response
-        let response6 = "";
-        let result6 = llm_resp_parse_words(response6);
-        assert_eq!(result6, Vec::<String>::new());
+        assert!(parse_words("").is_err());
 
         // Test case 7: Response with empty words section
-        let response7 = "<words>\n</words>";
-        let result7 = llm_resp_parse_words(response7);
-        assert_eq!(result7, Vec::<String>::new());
+        assert_eq!(
+            parse_words("<words>\n</words>").unwrap(),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_parse_recorded_responses() {
+        let words = parse_words(include_str!("fixtures/words_bullets.txt")).unwrap();
+        assert_eq!(words, vec!["quiero", "comer", "manzanas"]);
+
+        let sentence = NewSentence::parse(include_str!("fixtures/new_sentence.txt")).unwrap();
+        assert_eq!(sentence.text, "Mañana quiero comer en la cocina.");
+        assert_eq!(sentence.new_word, "mañana");
+        assert_eq!(sentence.translation, "tomorrow");
+
+        let result = NewSentence::parse(include_str!("fixtures/new_sentence_truncated.txt"));
+        assert!(matches!(result, Err(AppError::LlmError(e)) if e == "<translation> is missing"));
+    }
+
+    #[tokio::test]
+    async fn test_conjugate() {
+        let table = |first: &str| {
+            format!(
+                "<conjugation>\n- yo: {}\n- tú: hablaste\n- él: habló\n- nosotros: hablamos\n\
+                 - vosotros: hablasteis\n- ellos: hablaron\n</conjugation>",
+                first
+            )
+        };
+        let agent = Agent::new(
+            Language::new("spanish"),
+            Provider::Mock(MockProvider::new([table("hablé (yo)"), table("hablé")])),
+        );
+
+        let conjugation = agent
+            .conjugate("hablar", "pretérito indefinido")
+            .await
+            .unwrap();
+
+        // The first table has a note in a form, so it's generated again.
+        assert_eq!(
+            conjugation.forms[0],
+            ("yo".to_string(), "hablé".to_string())
+        );
+        assert_eq!(conjugation.forms.len(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_gen_new_sentence_response_is_repaired() {
+        let llm = MockProvider::new([
+            include_str!("fixtures/new_sentence_truncated.txt"),
+            include_str!("fixtures/new_sentence.txt"),
+        ]);
+
+        let sentence: NewSentence = prompt_parsed(&llm, "prompt").await.unwrap();
+
+        assert_eq!(sentence.new_word, "mañana");
+        assert!(llm.prompts()[1].contains("<translation> is missing"));
     }
 }
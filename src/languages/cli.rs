@@ -1,16 +1,213 @@
 //! Usage:
-//! cargo run --bin=lang --features=ssr
+//! cargo run --bin=lang --features=ssr -- vocab list
+//! cargo run --bin=lang --features=ssr -- --lang french sentence --save
+//! cargo run --bin=lang --features=ssr -- help
 
 #![cfg(feature = "ssr")]
 
+use clap::{Parser, Subcommand};
 use flashcard_app::languages::ai;
-use flashcard_app::settings::Language;
+use flashcard_app::languages::conjugation::{parse_conjugations, validate};
+use flashcard_app::languages::morphology::parse_lemmas;
+use flashcard_app::languages::sentence_card::create_sentence_card;
+use flashcard_app::languages::translation::{parse_dictionary, Translator, TranslatorChain};
+use flashcard_app::settings::{Language, Settings};
+use flashcard_app::words_db;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(about = "Learn languages with flashcards and LLMs")]
+struct Cli {
+    /// Overrides the learning language from the settings.
+    #[arg(long, global = true)]
+    lang: Option<Language>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage the words I already know.
+    Vocab {
+        #[command(subcommand)]
+        command: VocabCommand,
+    },
+    /// Add the words used in flashcards to the vocabulary.
+    Populate {
+        /// Defaults to the learning language, e.g. "spanish".
+        #[arg(long)]
+        from_tag: Option<String>,
+    },
+    /// Generate a sentence that introduces a new word.
+    Sentence {
+        /// Save the sentence as a flashcard.
+        #[arg(long)]
+        save: bool,
+    },
+    /// Write a short story using the words I know.
+    Story,
+}
+
+#[derive(Subcommand)]
+enum VocabCommand {
+    List,
+    Add {
+        word: String,
+        translation: Option<String>,
+    },
+    Delete {
+        word: String,
+    },
+    /// Translate the given words, or all the words without translation.
+    Translate {
+        words: Vec<String>,
+    },
+    /// Import a bilingual dictionary with a `word<TAB>translation` per line, used for offline
+    /// translations. Replaces the dictionary imported before.
+    ImportDictionary {
+        file: PathBuf,
+    },
+    /// Import a lemma list with a `lemma<TAB>form` per line, used to match inflected words with
+    /// the dictionary form.
+    ImportLemmas {
+        file: PathBuf,
+    },
+    /// Import conjugation tables with a `verb<TAB>tense<TAB>person<TAB>form` per line, used for
+    /// conjugation drills.
+    ImportConjugations {
+        file: PathBuf,
+    },
+}
 
 #[tokio::main]
-async fn main() -> Result<(), anyhow::Error> {
-    ai::Agent::new(Language::Spanish, "")
-        .populate_words_db()
-        .await
-        .unwrap();
+async fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    Settings::load_with(|settings| {
+        if let Some(lang) = cli.lang {
+            settings.learning_language = lang;
+        }
+    })?;
+
+    match cli.command {
+        Command::Vocab { command } => vocab(command).await,
+        Command::Populate { from_tag } => {
+            let agent = ai::Agent::from_settings();
+            let tag = from_tag.unwrap_or_else(|| agent.lang.to_string());
+            let words_before = words_db!().all_words()?.len();
+            agent.populate_words_db_from_tag(&tag).await?;
+            let words_after = words_db!().all_words()?.len();
+            println!("Added {} new words", words_after - words_before);
+            Ok(())
+        }
+        Command::Sentence { save } => {
+            let agent = ai::Agent::from_settings();
+            let sentence = agent.gen_new_sentence().await?;
+            println!("{}", sentence.text);
+            println!("New word: {} - {}", sentence.new_word, sentence.translation);
+            if save {
+                save_sentence(&sentence.text, &agent.lang).await?;
+            }
+            Ok(())
+        }
+        Command::Story => {
+            let story = ai::Agent::from_settings()
+                .gen_story(&Settings::get().stories)
+                .await?;
+            println!("{}", story.text);
+            println!("\nKnown words: {:.0}%", story.coverage.known_ratio * 100.0);
+            if !story.coverage.unknown_words.is_empty() {
+                println!("Unknown: {}", story.coverage.unknown_words.join(", "));
+            }
+            Ok(())
+        }
+    }
+}
+
+async fn vocab(command: VocabCommand) -> Result<(), Box<dyn Error>> {
+    match command {
+        VocabCommand::List => {
+            for word in words_db!().all_words()? {
+                println!(
+                    "{:<20} {:<30} {}",
+                    word.word,
+                    word.translation.unwrap_or_default(),
+                    word.forms.join(", ")
+                );
+            }
+        }
+        VocabCommand::Add { word, translation } => {
+            words_db!().add_word(&word, &translation.unwrap_or_default())?;
+        }
+        VocabCommand::Delete { word } => {
+            words_db!().delete_word(&word)?;
+        }
+        VocabCommand::Translate { words } => {
+            let words = if words.is_empty() {
+                words_db!()
+                    .all_words()?
+                    .into_iter()
+                    .filter(|word| word.translation.as_deref().unwrap_or_default().is_empty())
+                    .map(|word| word.word)
+                    .collect()
+            } else {
+                words
+            };
+
+            let translator =
+                TranslatorChain::from_settings(&Settings::get().learning_language.info()?)?;
+            for word in words {
+                let Some(translation) = translator.translate(&word).await? else {
+                    println!("{:<20} (no translation)", word);
+                    continue;
+                };
+                println!("{:<20} {}", word, translation);
+                words_db!().update_word_translation(&word, &translation)?;
+            }
+        }
+        VocabCommand::ImportDictionary { file } => {
+            let entries = parse_dictionary(&fs::read_to_string(&file)?);
+            let count = words_db!().import_dictionary(entries)?;
+            println!("Imported {} dictionary entries", count);
+        }
+        VocabCommand::ImportLemmas { file } => {
+            let entries = parse_lemmas(&fs::read_to_string(&file)?);
+            let count = words_db!().import_lemmas(entries)?;
+            println!("Imported {} word forms", count);
+        }
+        VocabCommand::ImportConjugations { file } => {
+            let info = Settings::get().learning_language.info()?;
+            let mut count = 0;
+            for mut conjugation in parse_conjugations(&fs::read_to_string(&file)?) {
+                conjugation
+                    .forms
+                    .sort_by_key(|(person, _)| info.persons.iter().position(|p| p == person));
+                if let Err(e) = validate(&conjugation, &info) {
+                    eprintln!(
+                        "Skipping {} ({}): {}",
+                        conjugation.verb, conjugation.tense, e
+                    );
+                    continue;
+                }
+                words_db!().save_conjugation(&conjugation)?;
+                count += 1;
+            }
+            println!("Imported {} conjugation tables", count);
+        }
+    }
+    Ok(())
+}
+
+/// Asks for the question, just like the "Create flashcard" button does in the web app.
+async fn save_sentence(sentence: &str, lang: &Language) -> Result<(), Box<dyn Error>> {
+    print!("Question (the sentence in English, empty to translate it): ");
+    io::stdout().flush()?;
+    let mut question = String::new();
+    io::stdin().read_line(&mut question)?;
+
+    let id = create_sentence_card(lang, sentence, Some(question)).await?;
+    println!("Added card #{}", id);
     Ok(())
 }
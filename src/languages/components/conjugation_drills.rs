@@ -0,0 +1,195 @@
+use leptos::prelude::*;
+use thaw::Spinner;
+
+use crate::components::ShowError;
+use crate::errors::AppError;
+#[cfg(feature = "ssr")]
+use crate::languages::ai::Agent;
+use crate::languages::components::SelectedLanguage;
+#[cfg(feature = "ssr")]
+use crate::languages::conjugation::{conjugate, save_drills, tense_tag};
+use crate::languages::model::Conjugation;
+use crate::settings::Language;
+
+#[server(GetTenses, "/api")]
+async fn get_tenses(lang: Language) -> Result<Vec<String>, AppError> {
+    Ok(lang.info()?.tenses)
+}
+
+#[server(ConjugateVerb, "/api")]
+async fn conjugate_verb(
+    lang: Language,
+    verb: String,
+    tense: String,
+) -> Result<Conjugation, AppError> {
+    conjugate(&Agent::for_language(lang), &verb, &tense).await
+}
+
+/// Adds a drill card per person. Returns how many were added and the tag to review them.
+#[server(CreateDrills, "/api")]
+async fn create_drills(
+    lang: Language,
+    verb: String,
+    tense: String,
+) -> Result<(usize, String), AppError> {
+    let conjugation = conjugate(&Agent::for_language(lang.clone()), &verb, &tense).await?;
+    let count = save_drills(&conjugation, &lang)?;
+    Ok((count, tense_tag(&lang, &tense)))
+}
+
+/// Conjugation tables of verbs, turned into flashcards to drill the forms.
+#[component]
+pub fn ConjugationDrills() -> impl IntoView {
+    let show_error = ShowError::from_ctx();
+    let lang = SelectedLanguage::from_ctx();
+    let tenses = Resource::new(
+        move || lang.get(),
+        |lang| async move { get_tenses(lang).await.unwrap_or_default() },
+    );
+    let verb = RwSignal::new(String::new());
+    let tense = RwSignal::new(String::new());
+    let conjugation = RwSignal::new(None::<Conjugation>);
+    let drills = RwSignal::new(None::<(usize, String)>);
+
+    let get_table = Action::new(move |input: &(Language, String, String)| {
+        let (lang, verb, tense) = input.clone();
+        async move {
+            drills.set(None);
+            match conjugate_verb(lang, verb, tense).await {
+                Ok(table) => conjugation.set(Some(table)),
+                Err(e) => show_error.show(format!("Failed to conjugate:\n {}", e)),
+            }
+        }
+    });
+    let add_drills = Action::new(move |input: &(Language, String, String)| {
+        let (lang, verb, tense) = input.clone();
+        async move {
+            match create_drills(lang, verb, tense).await {
+                Ok(created) => drills.set(Some(created)),
+                Err(e) => show_error.show(format!("Failed to create drills:\n {}", e)),
+            }
+        }
+    });
+    let pending = move || get_table.pending().get() || add_drills.pending().get();
+
+    // Tenses are named in the language
+    Effect::new(move |_| {
+        lang.get();
+        tense.set(String::new());
+        conjugation.set(None);
+    });
+    let input = move || {
+        let tense = match tense.get_untracked() {
+            // The first tense is selected until another one is picked.
+            tense if tense.is_empty() => tenses
+                .get_untracked()
+                .and_then(|tenses| tenses.first().cloned())
+                .unwrap_or_default(),
+            tense => tense,
+        };
+        (lang.get_untracked(), verb.get_untracked(), tense)
+    };
+
+    view! {
+        <div class="flex flex-col gap-4 max-w-4xl mx-auto">
+            <h1 class="text-2xl font-bold text-center">Conjugation</h1>
+            <div class="flex flex-row gap-4">
+                <input
+                    class="border rounded px-3 py-2"
+                    type="text"
+                    placeholder="Verb, e.g. hablar"
+                    prop:value=move || verb.get()
+                    on:input=move |ev| verb.set(event_target_value(&ev))
+                />
+                <Transition fallback=move || {
+                    view! { <Spinner /> }
+                }>
+                    {move || Suspend::new(async move {
+                        let tenses = tenses.await;
+                        view! {
+                            <select
+                                class="border rounded px-3 py-2"
+                                on:change=move |ev| tense.set(event_target_value(&ev))
+                            >
+                                {tenses
+                                    .into_iter()
+                                    .map(|tense| {
+                                        view! { <option value=tense.clone()>{tense.clone()}</option> }
+                                    })
+                                    .collect_view()}
+                            </select>
+                        }
+                    })}
+                </Transition>
+                <button
+                    class="bg-blue-500 text-white px-4 py-2 rounded-md cursor-pointer disabled:opacity-50"
+                    disabled=move || pending() || verb.get().trim().is_empty()
+                    on:click=move |_| {
+                        get_table.dispatch(input());
+                    }
+                >
+                    Conjugate
+                </button>
+            </div>
+            <Show when=pending>
+                <Spinner />
+            </Show>
+
+            {move || {
+                conjugation
+                    .get()
+                    .map(|Conjugation { verb, tense, forms }| {
+                        view! {
+                            <table class="border-collapse border border-gray-300">
+                                <caption class="text-left font-semibold mb-2">
+                                    {format!("{}, {}", verb, tense)}
+                                </caption>
+                                <tbody>
+                                    {forms
+                                        .into_iter()
+                                        .map(|(person, form)| {
+                                            view! {
+                                                <tr>
+                                                    <td class="px-4 py-2 border text-gray-500">{person}</td>
+                                                    <td class="px-4 py-2 border">{form}</td>
+                                                </tr>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </tbody>
+                            </table>
+                            <button
+                                class="self-start bg-blue-500 text-white px-4 py-2 rounded-md cursor-pointer disabled:opacity-50"
+                                disabled=pending
+                                on:click=move |_| {
+                                    add_drills
+                                        .dispatch((
+                                            lang.get_untracked(),
+                                            verb.clone(),
+                                            tense.clone(),
+                                        ));
+                                }
+                            >
+                                Create drills
+                            </button>
+                        }
+                    })
+            }}
+
+            {move || {
+                drills
+                    .get()
+                    .map(|(count, tag)| {
+                        view! {
+                            <p>
+                                {format!("{} drills added. ", count)}
+                                <a class="text-blue-600 hover:underline" href=format!("/review-cards/{}", tag)>
+                                    "Review them"
+                                </a>
+                            </p>
+                        }
+                    })
+            }}
+        </div>
+    }
+}
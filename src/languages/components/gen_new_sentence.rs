@@ -4,14 +4,23 @@ use crate::components::ShowError;
 use crate::errors::AppError;
 #[cfg(feature = "ssr")]
 use crate::languages::ai;
+use crate::languages::components::{SelectedLanguage, SentenceCardButton};
 use crate::languages::model::NewSentence;
 use crate::settings::Language;
+#[cfg(feature = "ssr")]
+use crate::words_db;
 use thaw::Spinner;
 
+/// Generates a sentence and saves it to the library.
 #[server(GenerateSentence, "/api")]
-async fn generate_sentence() -> Result<(NewSentence, Language), AppError> {
-    let agent = ai::Agent::from_settings();
+async fn generate_sentence(lang: Language) -> Result<(NewSentence, Language), AppError> {
+    let agent = ai::Agent::for_language(lang);
     let sentence = agent.gen_new_sentence().await?;
+    {
+        let db = words_db!(agent.lang);
+        let coverage = db.coverage(&sentence.text, &agent.lang.info()?)?;
+        db.save_generated_sentence(&sentence, coverage.known_ratio)?;
+    }
     Ok((sentence, agent.lang))
 }
 
@@ -19,18 +28,22 @@ async fn generate_sentence() -> Result<(NewSentence, Language), AppError> {
 #[component]
 pub fn GenerateSentence() -> impl IntoView {
     let show_error = ShowError::from_ctx();
+    let lang = SelectedLanguage::from_ctx();
 
     let new_sentence = RwSignal::new(None);
-    let flashcard_tag = RwSignal::new("".to_string());
+    let sentence_language = RwSignal::new(Language::default());
 
-    let gen_sentence = Action::new(move |_input: &()| async move {
-        match generate_sentence().await {
-            Err(e) => {
-                show_error.show(format!("{:?}", e));
-            }
-            Ok((sentence, language)) => {
-                new_sentence.set(Some(sentence));
-                flashcard_tag.set(language.as_str().to_string());
+    let gen_sentence = Action::new(move |lang: &Language| {
+        let lang = lang.clone();
+        async move {
+            match generate_sentence(lang).await {
+                Err(e) => {
+                    show_error.show(format!("{:?}", e));
+                }
+                Ok((sentence, language)) => {
+                    new_sentence.set(Some(sentence));
+                    sentence_language.set(language);
+                }
             }
         }
     });
@@ -45,7 +58,7 @@ pub fn GenerateSentence() -> impl IntoView {
         <button
             class="bg-blue-500 text-white px-4 py-2 rounded-md cursor-pointer"
             on:click=move |_event| {
-                gen_sentence.dispatch(());
+                gen_sentence.dispatch(lang.get_untracked());
             }
         >
             Generate Sentence
@@ -89,22 +102,10 @@ pub fn GenerateSentence() -> impl IntoView {
             </table>
         </div>
 
-        <div class="mt-4">
-            <form action="/add-card">
-                <input
-                    type="hidden"
-                    name="answer"
-                    value=move || new_sentence.get().map(|s| s.text)
-                />
-                <input type="hidden" name="tag" value=move || flashcard_tag.get() />
-                <input type="hidden" name="source" value="learning-languages app" />
-                <button
-                    type="submit"
-                    class="bg-blue-500 text-white px-4 py-2 rounded-md cursor-pointer"
-                >
-                    Create flashcard
-                </button>
-            </form>
-        </div>
+        <SentenceCardButton
+            lang=sentence_language
+            sentence=Signal::derive(move || new_sentence.get().map(|s| s.text))
+            translation={ None::<String> }
+        />
     }
 }
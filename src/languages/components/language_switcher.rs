@@ -0,0 +1,93 @@
+use leptos::prelude::*;
+use leptos_router::components::Outlet;
+use thaw::Spinner;
+
+#[cfg(feature = "ssr")]
+use crate::settings::{all_languages, Settings};
+use crate::settings::{Language, LanguageInfo};
+
+/// All the languages and the default one.
+#[server(GetLanguages, "/api")]
+async fn get_languages() -> Result<(Vec<LanguageInfo>, Language), ServerFnError> {
+    Ok((all_languages(), Settings::get().learning_language.clone()))
+}
+
+/// The language picked in the switcher, shared by all the `/learn-languages` pages.
+#[derive(Clone, Copy)]
+pub struct SelectedLanguage(RwSignal<Language>);
+
+impl SelectedLanguage {
+    pub fn from_ctx() -> Self {
+        expect_context::<Self>()
+    }
+
+    pub fn get(&self) -> Language {
+        self.0.get()
+    }
+
+    pub fn get_untracked(&self) -> Language {
+        self.0.get_untracked()
+    }
+}
+
+/// Layout of the `/learn-languages` pages: a language switcher above the page.
+/// Starts with `learning_language` from the settings.
+#[component]
+pub fn LanguagesLayout() -> impl IntoView {
+    let languages = OnceResource::new(get_languages());
+
+    view! {
+        <Transition fallback=move || {
+            view! { <Spinner /> }
+        }>
+            {move || Suspend::new(async move {
+                let (languages, language) = languages.await.unwrap_or_default();
+                view! {
+                    <LanguageProvider language=language>
+                        <LanguageSwitcher languages=languages />
+                        <Outlet />
+                    </LanguageProvider>
+                }
+            })}
+        </Transition>
+    }
+}
+
+#[component]
+fn LanguageProvider(language: Language, children: Children) -> impl IntoView {
+    provide_context(SelectedLanguage(RwSignal::new(language)));
+    children()
+}
+
+#[component]
+fn LanguageSwitcher(languages: Vec<LanguageInfo>) -> impl IntoView {
+    let SelectedLanguage(selected) = SelectedLanguage::from_ctx();
+
+    view! {
+        <div class="flex justify-end p-4">
+            <select
+                class="border rounded px-3 py-2"
+                on:change=move |ev| {
+                    if let Ok(language) = event_target_value(&ev).parse::<Language>() {
+                        selected.set(language);
+                    }
+                }
+            >
+                {languages
+                    .into_iter()
+                    .map(|info| {
+                        let name = info.name.clone();
+                        view! {
+                            <option
+                                value=info.name.to_string()
+                                selected=move || selected.with(|selected| *selected == name)
+                            >
+                                {info.display_name}
+                            </option>
+                        }
+                    })
+                    .collect_view()}
+            </select>
+        </div>
+    }
+}
@@ -0,0 +1,231 @@
+use leptos::prelude::*;
+use thaw::Spinner;
+
+use crate::components::ShowError;
+use crate::errors::AppError;
+use crate::languages::components::{SelectedLanguage, Story};
+use crate::languages::model::{Coverage, SavedSentence, SavedStory};
+#[cfg(feature = "ssr")]
+use crate::languages::translation::{Translator, TranslatorChain};
+use crate::settings::Language;
+#[cfg(feature = "ssr")]
+use crate::words_db;
+
+#[server(GetLibrary, "/api")]
+async fn get_library(lang: Language) -> Result<(Vec<SavedStory>, Vec<SavedSentence>), AppError> {
+    let db = words_db!(lang);
+    Ok((db.all_stories()?, db.all_generated_sentences()?))
+}
+
+/// The story with its coverage by my current vocabulary.
+#[server(GetStory, "/api")]
+async fn get_story(lang: Language, id: i64) -> Result<Option<(SavedStory, Coverage)>, AppError> {
+    let info = lang.info()?;
+    let db = words_db!(lang);
+    let Some(story) = db.get_story(id)? else {
+        return Ok(None);
+    };
+    let coverage = db.coverage(&story.text, &info)?;
+    Ok(Some((story, coverage)))
+}
+
+/// Adds the words looked up in the story that are not in my vocabulary yet, with their
+/// translations. Returns how many were added.
+#[server(AddLookedUpWords, "/api")]
+async fn add_looked_up_words(lang: Language, id: i64) -> Result<usize, AppError> {
+    let new_words = {
+        let db = words_db!(lang);
+        let Some(story) = db.get_story(id)? else {
+            return Ok(0);
+        };
+        let mut new_words = Vec::new();
+        for word in story.looked_up {
+            if db.find_word(&word)?.is_none() {
+                new_words.push(word);
+            }
+        }
+        new_words
+    };
+
+    let translator = TranslatorChain::from_settings(&lang.info()?)?;
+    for word in new_words.iter() {
+        let translation = translator.translate(word).await?.unwrap_or_default();
+        words_db!(lang).add_word(word, &translation)?;
+    }
+    Ok(new_words.len())
+}
+
+/// Stories and sentences generated before, and imported texts, to re-read them.
+#[component]
+pub fn Library() -> impl IntoView {
+    let lang = SelectedLanguage::from_ctx();
+    let library = Resource::new(
+        move || lang.get(),
+        |lang| async move { get_library(lang).await.unwrap_or_default() },
+    );
+    let selected_story = RwSignal::new(None::<i64>);
+
+    // Stories of another language have other ids
+    Effect::new(move |_| {
+        lang.get();
+        selected_story.set(None);
+    });
+
+    view! {
+        <div class="flex flex-col max-w-4xl mx-auto">
+            <h1 class="text-2xl font-bold text-center">Library</h1>
+
+            {move || selected_story.get().map(|id| view! { <ReadStory id=id /> })}
+
+            <Transition fallback=move || {
+                view! { <Spinner /> }
+            }>
+                {move || Suspend::new(async move {
+                    let (stories, sentences) = library.await;
+                    view! {
+                        <h2 class="text-xl font-semibold mt-6 mb-2">Stories and texts</h2>
+                        <ul class="flex flex-col gap-2">
+                            {stories
+                                .into_iter()
+                                .map(|story| {
+                                    let id = story.id;
+                                    view! {
+                                        <li
+                                            class="p-2 rounded border hover:bg-gray-100 cursor-pointer"
+                                            on:click=move |_| selected_story.set(Some(id))
+                                        >
+                                            <div class="text-sm text-gray-500">
+                                                {story.created_at.format("%Y-%m-%d %H:%M").to_string()}
+                                                {format!(
+                                                    " · Known words: {:.0}% · Looked up: {}",
+                                                    story.known_ratio * 100.0,
+                                                    story.looked_up.len(),
+                                                )}
+                                            </div>
+                                            <div class="truncate">
+                                                <span class="font-semibold">
+                                                    {story.title.map(|title| format!("{}: ", title))}
+                                                </span>
+                                                {story.text}
+                                            </div>
+                                        </li>
+                                    }
+                                })
+                                .collect_view()}
+                        </ul>
+
+                        <h2 class="text-xl font-semibold mt-6 mb-2">Sentences</h2>
+                        <table class="w-full border-collapse border border-gray-300">
+                            <thead class="bg-gray-50">
+                                <tr>
+                                    <th class="px-4 py-2 border text-left">Date</th>
+                                    <th class="px-4 py-2 border text-left">Sentence</th>
+                                    <th class="px-4 py-2 border text-left">New Word</th>
+                                    <th class="px-4 py-2 border text-left">Translation</th>
+                                    <th class="px-4 py-2 border text-left">Known words</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {sentences
+                                    .into_iter()
+                                    .map(|saved| {
+                                        view! {
+                                            <tr class="hover:bg-gray-50">
+                                                <td class="px-4 py-2 border">
+                                                    {saved.created_at.format("%Y-%m-%d %H:%M").to_string()}
+                                                </td>
+                                                <td class="px-4 py-2 border">{saved.sentence.text}</td>
+                                                <td class="px-4 py-2 border">
+                                                    {saved.sentence.new_word}
+                                                </td>
+                                                <td class="px-4 py-2 border">
+                                                    {saved.sentence.translation}
+                                                </td>
+                                                <td class="px-4 py-2 border">
+                                                    {format!("{:.0}%", saved.known_ratio * 100.0)}
+                                                </td>
+                                            </tr>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </tbody>
+                        </table>
+                    }
+                })}
+            </Transition>
+        </div>
+    }
+}
+
+/// A story from the library, with the words I still don't know underlined.
+#[component]
+pub fn ReadStory(id: i64) -> impl IntoView {
+    let show_error = ShowError::from_ctx();
+    let lang = SelectedLanguage::from_ctx();
+    let story = Resource::new(
+        move || lang.get(),
+        move |lang| async move { (get_story(lang.clone(), id).await.ok().flatten(), lang) },
+    );
+
+    let add_words = Action::new(move |lang: &Language| {
+        let lang = lang.clone();
+        async move {
+            match add_looked_up_words(lang, id).await {
+                Ok(_) => story.refetch(),
+                Err(e) => show_error.show(format!("Failed to add words:\n {}", e)),
+            }
+        }
+    });
+
+    view! {
+        <Transition fallback=move || {
+            view! { <Spinner /> }
+        }>
+            {move || Suspend::new(async move {
+                let (story, learning_language) = story.await;
+                story
+                    .map(|(story, coverage)| {
+                        let new_words = story
+                            .looked_up
+                            .iter()
+                            .filter(|word| coverage.unknown_words.contains(word))
+                            .cloned()
+                            .collect::<Vec<_>>();
+                        let lang = learning_language.clone();
+                        view! {
+                            <p class="text-center text-sm text-gray-500 mt-4">
+                                {format!(
+                                    "{} · Known words: {:.0}%, when written: {:.0}%",
+                                    story.created_at.format("%Y-%m-%d %H:%M"),
+                                    coverage.known_ratio * 100.0,
+                                    story.known_ratio * 100.0,
+                                )}
+                            </p>
+                            <Story
+                                story=story.text
+                                learning_language=learning_language
+                                unknown_words=coverage.unknown_words
+                                story_id=story.id
+                            />
+                            {(!new_words.is_empty())
+                                .then(|| {
+                                    view! {
+                                        <div class="mt-4 flex items-center gap-4">
+                                            <span>"Looked up: " {new_words.join(", ")}</span>
+                                            <button
+                                                class="bg-blue-500 text-white px-4 py-2 rounded-md cursor-pointer"
+                                                on:click=move |_| {
+                                                    add_words.dispatch(lang.clone());
+                                                }
+                                            >
+                                                Add to vocabulary
+                                            </button>
+                                        </div>
+                                    }
+                                })}
+                        }
+                    })
+            })}
+        </Transition>
+    }
+}
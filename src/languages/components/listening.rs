@@ -0,0 +1,260 @@
+use leptos::prelude::*;
+use thaw::Spinner;
+
+use crate::components::ShowError;
+#[cfg(feature = "ssr")]
+use crate::db::Database as FlashcardsDb;
+use crate::errors::AppError;
+use crate::languages::components::SelectedLanguage;
+#[cfg(feature = "ssr")]
+use crate::languages::listening::{cards_to_listen, dictation_score, record_listening};
+use crate::languages::model::{ListeningCard, ListeningReview};
+use crate::settings::Language;
+
+/// The text-to-speech voice and the cards to listen to.
+#[server(GetListeningCards, "/api")]
+async fn get_listening_cards(lang: Language) -> Result<(String, Vec<ListeningCard>), AppError> {
+    let info = lang.info()?;
+    let voice = info.tts_voice.unwrap_or(info.iso_code);
+    Ok((voice, cards_to_listen(&lang)?))
+}
+
+/// Scores the sentence typed by ear against the answer of the card.
+#[server(SubmitDictation, "/api")]
+async fn submit_dictation(
+    lang: Language,
+    card_id: i64,
+    heard: String,
+) -> Result<ListeningReview, AppError> {
+    let card = {
+        let db = FlashcardsDb::get_instance().unwrap().lock().unwrap();
+        db.get_card(card_id)
+            .map_err(|e| AppError::DuckdbError(e.to_string()))?
+    };
+    record_listening(&lang, card_id, dictation_score(&heard, &card.answer))
+}
+
+#[server(SubmitTranslation, "/api")]
+async fn submit_translation(
+    lang: Language,
+    card_id: i64,
+    translation: String,
+) -> Result<ListeningReview, AppError> {
+    let card = {
+        let db = FlashcardsDb::get_instance().unwrap().lock().unwrap();
+        db.get_card(card_id)
+            .map_err(|e| AppError::DuckdbError(e.to_string()))?
+    };
+    let score = if translation == card.question {
+        1.0
+    } else {
+        0.0
+    };
+    record_listening(&lang, card_id, score)
+}
+
+/// Reads the text aloud with the speech synthesis of the browser.
+fn speak(text: &str, voice: &str, rate: f32) {
+    let Ok(synthesis) = window().speech_synthesis() else {
+        return;
+    };
+    let Ok(utterance) = web_sys::SpeechSynthesisUtterance::new_with_text(text) else {
+        return;
+    };
+    utterance.set_lang(voice);
+    utterance.set_rate(rate);
+    synthesis.cancel();
+    synthesis.speak(&utterance);
+}
+
+/// Reviews sentence cards by ear: the answer is played, then typed, or its translation picked.
+#[component]
+pub fn Listening() -> impl IntoView {
+    let lang = SelectedLanguage::from_ctx();
+    let cards = Resource::new(
+        move || lang.get(),
+        |lang| async move { get_listening_cards(lang).await },
+    );
+
+    view! {
+        <div class="flex flex-col gap-4 max-w-2xl mx-auto">
+            <h1 class="text-2xl font-bold text-center">Listening</h1>
+            <Transition fallback=move || {
+                view! { <Spinner /> }
+            }>
+                {move || Suspend::new(async move {
+                    match cards.await {
+                        Ok((voice, cards)) => {
+                            view! { <ListeningCards voice=voice cards=cards /> }.into_any()
+                        }
+                        Err(e) => {
+                            view! { <p class="text-red-600">{format!("Failed to load cards: {}", e)}</p> }
+                                .into_any()
+                        }
+                    }
+                })}
+            </Transition>
+        </div>
+    }
+}
+
+#[component]
+fn ListeningCards(voice: String, cards: Vec<ListeningCard>) -> impl IntoView {
+    let show_error = ShowError::from_ctx();
+    let lang = SelectedLanguage::from_ctx();
+    let total = cards.len();
+    let cards = StoredValue::new(cards);
+    let voice = StoredValue::new(voice);
+    let current_index = RwSignal::new(0usize);
+    let pick_translation = RwSignal::new(false);
+    let heard = RwSignal::new(String::new());
+    let review = RwSignal::new(None::<ListeningReview>);
+
+    let card_at = move |index: usize| cards.with_value(|cards| cards.get(index).cloned());
+    let play = move |rate: f32| {
+        if let Some(ListeningCard { card, .. }) = card_at(current_index.get_untracked()) {
+            // Markdown emphasis isn't read aloud.
+            voice.with_value(|voice| speak(&card.answer.replace(['*', '_', '`'], ""), voice, rate));
+        }
+    };
+
+    let submit = Action::new(move |input: &(Language, i64, Option<String>)| {
+        let (lang, card_id, translation) = input.clone();
+        let heard = heard.get_untracked();
+        async move {
+            let result = match translation {
+                Some(translation) => submit_translation(lang, card_id, translation).await,
+                None => submit_dictation(lang, card_id, heard).await,
+            };
+            match result {
+                Ok(result) => review.set(Some(result)),
+                Err(e) => show_error.show(format!("Failed to submit the answer:\n {}", e)),
+            }
+        }
+    });
+    let next = move |_| {
+        review.set(None);
+        heard.set(String::new());
+        current_index.update(|i| *i += 1);
+        play(1.0);
+    };
+
+    view! {
+        <progress
+            class="w-full h-2.5 rounded-full"
+            value=move || if total == 0 { 0 } else { current_index.get() + 1 }
+            max=total
+        ></progress>
+        <label class="flex items-center justify-end gap-2 text-sm text-slate-600">
+            <input
+                type="checkbox"
+                prop:checked=move || pick_translation.get()
+                on:change=move |ev| pick_translation.set(event_target_checked(&ev))
+            />
+            "Pick the translation instead of typing"
+        </label>
+        {move || match card_at(current_index.get()) {
+            None => view! { <p class="text-center">"Nothing to listen to at the moment."</p> }.into_any(),
+            Some(ListeningCard { card, translations }) => {
+                let card_id = card.id;
+                view! {
+                    <div class="bg-white border border-slate-200 rounded-lg p-6 shadow flex flex-col gap-4">
+                        <div class="flex gap-4">
+                            <button
+                                class="bg-blue-500 text-white px-4 py-2 rounded-md cursor-pointer"
+                                on:click=move |_| play(1.0)
+                            >
+                                "Play"
+                            </button>
+                            <button
+                                class="bg-blue-100 text-blue-700 px-4 py-2 rounded-md cursor-pointer"
+                                on:click=move |_| play(0.6)
+                            >
+                                "Play slowly"
+                            </button>
+                        </div>
+                        <Show
+                            when=move || review.get().is_none()
+                            fallback=move || {
+                                let review = review.get().unwrap();
+                                view! {
+                                    <p class="font-semibold">
+                                        {format!(
+                                            "{:.0}% - {}",
+                                            review.score * 100.0,
+                                            review.grade.as_str(),
+                                        )}
+                                    </p>
+                                }
+                            }
+                        >
+                            {
+                                let translations = translations.clone();
+                                move || {
+                                    if pick_translation.get() {
+                                        translations
+                                            .clone()
+                                            .into_iter()
+                                            .map(|translation| {
+                                                let picked = translation.clone();
+                                                view! {
+                                                    <button
+                                                        class="text-left border rounded-md px-4 py-2 cursor-pointer hover:bg-gray-100 disabled:opacity-50"
+                                                        disabled=move || submit.pending().get()
+                                                        on:click=move |_| {
+                                                            submit
+                                                                .dispatch((
+                                                                    lang.get_untracked(),
+                                                                    card_id,
+                                                                    Some(picked.clone()),
+                                                                ));
+                                                        }
+                                                    >
+                                                        {translation}
+                                                    </button>
+                                                }
+                                            })
+                                            .collect_view()
+                                            .into_any()
+                                    } else {
+                                        view! {
+                                            <input
+                                                class="border rounded px-3 py-2"
+                                                type="text"
+                                                placeholder="Type what you hear"
+                                                prop:value=move || heard.get()
+                                                on:input=move |ev| heard.set(event_target_value(&ev))
+                                            />
+                                            <button
+                                                class="self-start bg-blue-500 text-white px-4 py-2 rounded-md cursor-pointer disabled:opacity-50"
+                                                disabled=move || submit.pending().get()
+                                                on:click=move |_| {
+                                                    submit.dispatch((lang.get_untracked(), card_id, None));
+                                                }
+                                            >
+                                                "Check"
+                                            </button>
+                                        }
+                                            .into_any()
+                                    }
+                                }
+                            }
+                        </Show>
+                        <Show when=move || review.get().is_some()>
+                            <hr />
+                            <p class="text-lg">{card.answer.clone()}</p>
+                            <p class="text-gray-500">{card.question.clone()}</p>
+                            <button
+                                class="self-start bg-blue-500 text-white px-4 py-2 rounded-md cursor-pointer"
+                                on:click=next
+                            >
+                                "Next"
+                            </button>
+                        </Show>
+                    </div>
+                }
+                    .into_any()
+            }
+        }}
+    }
+}
@@ -1,9 +1,23 @@
+mod conjugation_drills;
 mod gen_new_sentence;
+mod language_switcher;
+mod library;
+mod listening;
 mod overview;
+mod read_text;
+mod sentence_card;
+mod story;
 mod vocabulary;
 mod write_story;
 
+pub use conjugation_drills::ConjugationDrills;
 pub use gen_new_sentence::GenerateSentence;
+pub use language_switcher::{LanguagesLayout, SelectedLanguage};
+pub use library::{Library, ReadStory};
+pub use listening::Listening;
 pub use overview::Overview;
+pub use read_text::ReadText;
+pub use sentence_card::SentenceCardButton;
+pub use story::Story;
 pub use vocabulary::Vocabulary;
 pub use write_story::WriteStory;
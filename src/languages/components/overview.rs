@@ -25,6 +25,33 @@ pub fn Overview() -> impl IntoView {
             >
                 Write a story
             </a>
+            <a
+                class="w-full h-16 bg-gray-100 hover:bg-gray-200 text-black font-semibold text-lg rounded-xl transition-colors duration-200 shadow-lg hover:shadow-xl flex items-center justify-center"
+                href="/learn-languages/library"
+            >
+                Library
+            </a>
+        </div>
+
+        <div class="flex flex-row gap-4 p-4">
+            <a
+                class="w-full h-16 bg-gray-100 hover:bg-gray-200 text-black font-semibold text-lg rounded-xl transition-colors duration-200 shadow-lg hover:shadow-xl flex items-center justify-center"
+                href="/learn-languages/read"
+            >
+                Read a text
+            </a>
+            <a
+                class="w-full h-16 bg-gray-100 hover:bg-gray-200 text-black font-semibold text-lg rounded-xl transition-colors duration-200 shadow-lg hover:shadow-xl flex items-center justify-center"
+                href="/learn-languages/conjugation"
+            >
+                Conjugation
+            </a>
+            <a
+                class="w-full h-16 bg-gray-100 hover:bg-gray-200 text-black font-semibold text-lg rounded-xl transition-colors duration-200 shadow-lg hover:shadow-xl flex items-center justify-center"
+                href="/learn-languages/listen"
+            >
+                Listen
+            </a>
         </div>
     }
 }
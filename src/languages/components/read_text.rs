@@ -0,0 +1,214 @@
+use base64::prelude::*;
+use js_sys::Uint8Array;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use server_fn::codec::Json;
+use thaw::Spinner;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::components::ShowError;
+use crate::errors::AppError;
+use crate::languages::components::{ReadStory, SelectedLanguage};
+#[cfg(feature = "ssr")]
+use crate::languages::epub::parse_epub;
+use crate::settings::Language;
+#[cfg(feature = "ssr")]
+use crate::words_db;
+
+/// Saves the text to the library. Returns its id.
+#[server(ImportText, "/api")]
+async fn import_text(lang: Language, title: String, text: String) -> Result<i64, AppError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(AppError::InvalidFile("The text is empty".to_string()));
+    }
+    let title = match title.trim() {
+        "" => text
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .take(40)
+            .collect(),
+        title => title.to_string(),
+    };
+    let info = lang.info()?;
+    let db = words_db!(lang);
+    let coverage = db.coverage(text, &info)?;
+    Ok(db.save_story(Some(&title), text, coverage.known_ratio)?)
+}
+
+/// Saves each chapter of the book, the EPUB file in base64, to the library. Returns their ids.
+// Bytes would be a JSON array of numbers, about 4 times the size of the file.
+#[server(name = ImportBook, prefix = "/api", input = Json)]
+async fn import_book(
+    lang: Language,
+    file_name: String,
+    book: String,
+) -> Result<Vec<i64>, AppError> {
+    let book = BASE64_STANDARD
+        .decode(book)
+        .map_err(|e| AppError::InvalidFile(e.to_string()))?;
+    let book = parse_epub(&book).map_err(AppError::InvalidFile)?;
+    let title = book.title.unwrap_or(file_name);
+    let info = lang.info()?;
+    let db = words_db!(lang);
+    let mut chapters = Vec::new();
+    for chapter in book.chapters {
+        let coverage = db.coverage(&chapter, &info)?;
+        chapters.push((chapter, coverage.known_ratio));
+    }
+    Ok(db.save_book(&title, &chapters)?)
+}
+
+/// Reads a pasted text, a `.txt` file or an `.epub` book in the learning language,
+/// with the same translations as in stories.
+#[component]
+pub fn ReadText() -> impl IntoView {
+    let show_error = ShowError::from_ctx();
+    let lang = SelectedLanguage::from_ctx();
+    let title = RwSignal::new(String::new());
+    let text = RwSignal::new(String::new());
+    let reading = RwSignal::new(None::<i64>);
+    let chapters = RwSignal::new(0);
+
+    // Texts of another language have other ids
+    Effect::new(move |_| {
+        lang.get();
+        reading.set(None);
+        chapters.set(0);
+    });
+
+    let import = Action::new(move |input: &(Language, String, String)| {
+        let (lang, title, text) = input.clone();
+        async move {
+            match import_text(lang, title, text).await {
+                Ok(id) => reading.set(Some(id)),
+                Err(e) => show_error.show(format!("Failed to import the text:\n {}", e)),
+            }
+        }
+    });
+    let import_epub = Action::new(move |input: &(Language, String, String)| {
+        let (lang, file_name, book) = input.clone();
+        async move {
+            match import_book(lang, file_name, book).await {
+                Ok(ids) => {
+                    chapters.set(ids.len());
+                    reading.set(ids.first().copied());
+                }
+                Err(e) => show_error.show(format!("Failed to import the book:\n {}", e)),
+            }
+        }
+    });
+    let importing = move || import.pending().get() || import_epub.pending().get();
+
+    // Texts are put in the text area, books are imported right away
+    let read_file = move |ev: leptos::ev::Event| {
+        let Some(file) = event_target::<web_sys::HtmlInputElement>(&ev)
+            .files()
+            .and_then(|files| files.get(0))
+        else {
+            return;
+        };
+        let file_name = file.name();
+        spawn_local(async move {
+            if file_name.to_lowercase().ends_with(".epub") {
+                match JsFuture::from(file.array_buffer()).await {
+                    Ok(buffer) => {
+                        let book = BASE64_STANDARD.encode(Uint8Array::new(&buffer).to_vec());
+                        import_epub.dispatch((lang.get_untracked(), file_name, book));
+                    }
+                    Err(e) => show_error.show(format!("Failed to read the file:\n {:?}", e)),
+                }
+            } else {
+                match JsFuture::from(file.text()).await {
+                    Ok(content) => {
+                        let name = file_name.rsplit_once('.').map(|(name, _)| name);
+                        title.set(name.unwrap_or(&file_name).to_string());
+                        text.set(content.as_string().unwrap_or_default());
+                    }
+                    Err(e) => show_error.show(format!("Failed to read the file:\n {:?}", e)),
+                }
+            }
+        });
+    };
+
+    view! {
+        <div class="flex flex-col gap-4 max-w-4xl mx-auto">
+            <h1 class="text-2xl font-bold text-center">Read</h1>
+            {move || match reading.get() {
+                Some(id) => {
+                    view! {
+                        <button
+                            class="self-start text-blue-600 hover:underline cursor-pointer"
+                            on:click=move |_| {
+                                reading.set(None);
+                                chapters.set(0);
+                            }
+                        >
+                            "Read another text"
+                        </button>
+                        {(chapters.get() > 1)
+                            .then(|| {
+                                view! {
+                                    <p class="text-sm text-gray-500">
+                                        {format!(
+                                            "Imported {} chapters, the next ones are in the library.",
+                                            chapters.get(),
+                                        )}
+                                    </p>
+                                }
+                            })}
+                        <ReadStory id=id />
+                    }
+                        .into_any()
+                }
+                None => {
+                    view! {
+                        <input
+                            class="border rounded px-3 py-2"
+                            type="text"
+                            placeholder="Title"
+                            prop:value=move || title.get()
+                            on:input=move |ev| title.set(event_target_value(&ev))
+                        />
+                        <textarea
+                            class="border rounded px-3 py-2"
+                            rows=12
+                            placeholder="Paste an article in the language you're learning"
+                            prop:value=move || text.get()
+                            on:input=move |ev| text.set(event_target_value(&ev))
+                        ></textarea>
+                        <label class="flex flex-col gap-2">
+                            <span>"Or open a .txt or .epub file:"</span>
+                            <input
+                                class="border rounded px-3 py-2"
+                                type="file"
+                                accept=".txt,.epub"
+                                on:change=read_file
+                            />
+                        </label>
+                        <button
+                            class="self-start bg-blue-500 text-white px-6 py-2 rounded hover:bg-blue-600 transition disabled:opacity-50"
+                            disabled=move || importing() || text.get().trim().is_empty()
+                            on:click=move |_| {
+                                import
+                                    .dispatch((
+                                        lang.get_untracked(),
+                                        title.get_untracked(),
+                                        text.get_untracked(),
+                                    ));
+                            }
+                        >
+                            "Read"
+                        </button>
+                        <Show when=importing>
+                            <Spinner />
+                        </Show>
+                    }
+                        .into_any()
+                }
+            }}
+        </div>
+    }
+}
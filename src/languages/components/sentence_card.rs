@@ -0,0 +1,78 @@
+use leptos::prelude::*;
+use thaw::Spinner;
+
+use crate::components::ShowError;
+use crate::errors::AppError;
+#[cfg(feature = "ssr")]
+use crate::languages::sentence_card;
+use crate::settings::Language;
+
+#[server(CreateSentenceCard, "/api")]
+async fn create_sentence_card(
+    lang: Language,
+    sentence: String,
+    translation: Option<String>,
+) -> Result<i64, AppError> {
+    sentence_card::create_sentence_card(&lang, &sentence, translation).await
+}
+
+/// Creates a flashcard for the sentence in one click, with a gloss of its words as examples.
+/// Without a `translation` the sentence is translated.
+#[component]
+pub fn SentenceCardButton(
+    #[prop(into)] lang: Signal<Language>,
+    #[prop(into)] sentence: Signal<Option<String>>,
+    #[prop(into)] translation: Signal<Option<String>>,
+) -> impl IntoView {
+    let show_error = ShowError::from_ctx();
+    let created_card = RwSignal::new(None::<i64>);
+    let create_card = Action::new(move |input: &(Language, String, Option<String>)| {
+        let (lang, sentence, translation) = input.clone();
+        async move {
+            match create_sentence_card(lang, sentence, translation).await {
+                Ok(id) => created_card.set(Some(id)),
+                Err(e) => show_error.show(format!("Failed to create the card:\n {}", e)),
+            }
+        }
+    });
+    let creating = create_card.pending();
+
+    // The created card is for the previous sentence
+    Effect::new(move |_| {
+        sentence.track();
+        created_card.set(None);
+    });
+
+    view! {
+        <div class="mt-4 flex items-center gap-4">
+            <button
+                class="bg-blue-500 text-white px-4 py-2 rounded-md cursor-pointer disabled:opacity-50"
+                disabled=move || creating.get() || sentence.with(Option::is_none)
+                on:click=move |_| {
+                    if let Some(sentence) = sentence.get_untracked() {
+                        create_card
+                            .dispatch((
+                                lang.get_untracked(),
+                                sentence,
+                                translation.get_untracked(),
+                            ));
+                    }
+                }
+            >
+                Create flashcard
+            </button>
+            {move || creating.get().then(|| view! { <Spinner /> })}
+            {move || {
+                created_card
+                    .get()
+                    .map(|id| {
+                        view! {
+                            <a class="text-blue-600 hover:underline" href=format!("/cards/{}", id)>
+                                {format!("Card #{} created", id)}
+                            </a>
+                        }
+                    })
+            }}
+        </div>
+    }
+}
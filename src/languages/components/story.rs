@@ -0,0 +1,253 @@
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use web_sys::window;
+
+use crate::errors::AppError;
+use crate::languages::components::SentenceCardButton;
+#[cfg(feature = "ssr")]
+use crate::languages::translation::{Translator, TranslatorChain};
+use crate::settings::Language;
+#[cfg(feature = "ssr")]
+use crate::words_db;
+
+/// A word or a sentence, from the vocabulary, the dictionary, the cache or an online translator.
+#[server(Translate, "/api")]
+async fn translate(lang: Language, text: String) -> Result<Option<String>, AppError> {
+    TranslatorChain::from_settings(&lang.info()?)?
+        .translate(&text)
+        .await
+}
+
+/// Same as `translate()`, but looking up a word also means that I don't know it well.
+/// The lookup is remembered with the story it was in.
+#[server(LookUpWord, "/api")]
+async fn look_up_word(
+    lang: Language,
+    word: String,
+    story_id: Option<i64>,
+) -> Result<Option<String>, AppError> {
+    {
+        let db = words_db!(lang);
+        db.record_lookup(&word)?;
+        if let Some(story_id) = story_id {
+            db.record_story_lookup(story_id, &word)?;
+        }
+    }
+    translate(lang, word).await
+}
+
+/// Story component that displays the story content with word hover functionality.
+/// The `unknown_words` are underlined. Hovering a word only translates it, clicking it looks it up:
+/// lookups are saved with the story when it has a `story_id`.
+#[component]
+pub fn Story(
+    #[prop(into)] story: Signal<String>,
+    #[prop(into)] learning_language: Language,
+    #[prop(optional)] unknown_words: Vec<String>,
+    #[prop(optional)] story_id: Option<i64>,
+) -> impl IntoView {
+    let learning_language = StoredValue::new(learning_language);
+    let unknown_words = StoredValue::new(unknown_words);
+    let (hovered_word, set_hovered_word) = signal(None::<String>);
+    let (translation, set_translation) = signal(None::<String>);
+    let (tooltip_pos, set_tooltip_pos) = signal((0.0, 0.0));
+    let (selected_sentence, set_selected_sentence) = signal(None::<String>);
+    let selected_translation = Resource::new(
+        move || selected_sentence.get(),
+        move |sentence| async move {
+            if let Some(sentence) = sentence {
+                translate(learning_language.get_value(), sentence)
+                    .await
+                    .unwrap_or_default()
+                    .unwrap_or_default()
+            } else {
+                "".to_string()
+            }
+        },
+    );
+
+    // Fetch translation when word changes. Passing over a word doesn't mean I don't know it,
+    // so it's not a lookup.
+    Effect::new(move |_| {
+        if let Some(word) = hovered_word.get() {
+            let clean_word = clean_word(&word);
+            if !clean_word.is_empty() {
+                spawn_local(async move {
+                    match translate(learning_language.get_value(), clean_word).await {
+                        Ok(trans) => set_translation.set(trans),
+                        Err(e) => web_sys::console::error_1(
+                            &format!("Error getting translation: {}", e).into(),
+                        ),
+                    }
+                });
+            }
+        } else {
+            set_translation.set(None);
+        }
+    });
+
+    let click_word = move |word: &str| {
+        let clean_word = clean_word(word);
+        if clean_word.is_empty() {
+            return;
+        }
+        spawn_local(async move {
+            match look_up_word(learning_language.get_value(), clean_word, story_id).await {
+                Ok(trans) => set_translation.set(trans),
+                Err(e) => web_sys::console::error_1(&format!("Error looking up: {}", e).into()),
+            }
+        });
+    };
+
+    // Function to get selected text
+    let get_selected_text = move || {
+        if let Some(window) = window() {
+            if let Some(selection) = window.get_selection().ok().flatten() {
+                let selected_text = String::from(selection.to_string()).trim().to_string();
+                if !selected_text.is_empty() {
+                    set_selected_sentence.set(Some(selected_text.to_string()));
+                } else {
+                    set_selected_sentence.set(None);
+                }
+            }
+        }
+    };
+
+    // Function to clear selection
+    let clear_selection = move || {
+        if let Some(window) = window() {
+            if let Some(selection) = window.get_selection().ok().flatten() {
+                let _ = selection.remove_all_ranges();
+                set_selected_sentence.set(None);
+            }
+        }
+    };
+    view! {
+        <div
+            class="mt-4 relative max-w-4xl mx-auto"
+            on:mouseup=move |_| get_selected_text()
+            on:mousedown=move |_| clear_selection()
+        >
+            {move || {
+                let story_content = story.get();
+                story_content
+                    .split("\n")
+                    .map(|line| line.to_string())
+                    .map(|line| {
+                        view! {
+                            <p>
+                                {line
+                                    .split_inclusive(" ")
+                                    .map(|word| word.to_string())
+                                    .map(|word| {
+                                        let word2 = word.clone();
+                                        let word3 = word.clone();
+                                        let unknown = unknown_words
+                                            .with_value(|unknown_words| is_unknown(&word, unknown_words));
+                                        view! {
+                                            <span
+                                                class="hover:bg-gray-100 cursor-pointer px-0.5 rounded relative select-text"
+                                                class=(
+                                                    ["underline", "decoration-dotted", "decoration-red-400"],
+                                                    unknown,
+                                                )
+                                                on:mouseenter=move |ev| {
+                                                    let rect = event_target::<web_sys::Element>(&ev)
+                                                        .get_bounding_client_rect();
+                                                    set_tooltip_pos
+                                                        .set((
+                                                            rect.left() + rect.width() / 2.0,
+                                                            rect.top() - (rect.height() * 1.5),
+                                                        ));
+                                                    set_hovered_word.set(Some(word2.clone()));
+                                                }
+                                                on:mouseleave=move |_| {
+                                                    set_hovered_word.set(None);
+                                                }
+                                                on:click=move |_| click_word(&word3)
+                                            >
+                                                {word}
+                                            </span>
+                                        }
+                                    })
+                                    .collect::<Vec<_>>()}
+                            </p>
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            }}
+
+            // Translation tooltip
+            {move || {
+                translation
+                    .get()
+                    .map(|trans| {
+                        let (x, y) = tooltip_pos.get();
+                        view! {
+                            <div
+                                class="fixed bg-black text-white px-2 py-1 rounded text-sm z-50 pointer-events-none shadow-lg"
+                                style=format!(
+                                    "left: {}px; top: {}px; transform: translateX(-50%);",
+                                    x,
+                                    y,
+                                )
+                            >
+                                {trans}
+                            </div>
+                        }
+                    })
+            }}
+        </div>
+
+        // Show selected sentence if any
+        {move || {
+            selected_sentence
+                .get()
+                .map(|sentence| {
+                    let sentence2 = sentence.clone();
+                    view! {
+                        <div class="mt-2 p-2 bg-gray-100 rounded relative w-full">
+                            <button
+                                class="absolute top-1 right-1 hover:text-blue-800 text-sm cursor-pointer"
+                                on:click=move |_| clear_selection()
+                            >
+                                X
+                            </button>
+                            <table class="w-full border-collapse">
+                                <thead>
+                                    <tr class="border-b">
+                                        <th class="text-left p-2 font-semibold">Selected</th>
+                                        <th class="text-left p-2 font-semibold">Translation</th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    <tr>
+                                        <td class="p-2">{sentence}</td>
+                                        <td class="p-2">{move || selected_translation.get()}</td>
+                                    </tr>
+                                </tbody>
+                            </table>
+                        </div>
+
+                        <SentenceCardButton
+                            lang=learning_language.get_value()
+                            sentence=Some(sentence2)
+                            translation=Signal::derive(move || selected_translation.get())
+                        />
+                    }
+                })
+        }}
+    }
+}
+
+fn clean_word(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphabetic())
+        .to_lowercase()
+}
+
+/// Whether the word, as written in the story, is one of the unknown words.
+fn is_unknown(word: &str, unknown_words: &[String]) -> bool {
+    word.split(|c: char| !c.is_alphanumeric() && c != '-')
+        .map(|part| part.trim_matches('-').to_lowercase())
+        .any(|part| unknown_words.contains(&part))
+}
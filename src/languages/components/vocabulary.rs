@@ -5,43 +5,55 @@ use crate::components::ShowError;
 use crate::errors::AppError;
 #[cfg(feature = "ssr")]
 use crate::languages::ai;
-use crate::languages::model::Word;
+use crate::languages::components::SelectedLanguage;
+use crate::languages::model::{Familiarity, Word};
+use crate::settings::Language;
 #[cfg(feature = "ssr")]
 use crate::words_db;
 
 #[server(GetWords, "/api")]
-async fn get_words() -> Result<Vec<Word>, AppError> {
-    Ok(words_db!().all_words()?)
+async fn get_words(lang: Language) -> Result<Vec<Word>, AppError> {
+    Ok(words_db!(lang).all_words()?)
 }
 
 #[server(UpdateWordTranslation, "/api")]
-async fn update_word_translation(word: String, translation: String) -> Result<(), AppError> {
-    words_db!().update_word_translation(&word, &translation)?;
+async fn update_word_translation(
+    lang: Language,
+    word: String,
+    translation: String,
+) -> Result<(), AppError> {
+    words_db!(lang).update_word_translation(&word, &translation)?;
     Ok(())
 }
 
 #[server(DeleteWord, "/api")]
-async fn delete_word(word: String) -> Result<(), AppError> {
-    words_db!().delete_word(&word)?;
+async fn delete_word(lang: Language, word: String) -> Result<(), AppError> {
+    words_db!(lang).delete_word(&word)?;
     Ok(())
 }
 
+/// Returns my other words with the same stem, in case the word is a form of one of them.
 #[server(AddWord, "/api")]
-async fn add_word(word: String, translation: String) -> Result<(), AppError> {
-    words_db!().add_word(&word, &translation)?;
-    Ok(())
+async fn add_word(
+    lang: Language,
+    word: String,
+    translation: String,
+) -> Result<Vec<String>, AppError> {
+    let db = words_db!(lang);
+    db.add_word(&word, &translation)?;
+    Ok(db.same_stem_words(&word)?)
 }
 
 #[server(AddFromFlashcards, "/api")]
-async fn add_from_flashcards() -> Result<(), AppError> {
-    let agent = ai::Agent::from_settings();
+async fn add_from_flashcards(lang: Language) -> Result<(), AppError> {
+    let agent = ai::Agent::for_language(lang);
     agent.populate_words_db().await?;
     Ok(())
 }
 
-fn refresh_words(set_words: WriteSignal<Vec<Word>>, show_error: ShowError) {
+fn refresh_words(lang: Language, set_words: WriteSignal<Vec<Word>>, show_error: ShowError) {
     spawn_local(async move {
-        match get_words().await {
+        match get_words(lang).await {
             Ok(updated_words) => {
                 set_words.set(updated_words);
             }
@@ -57,19 +69,22 @@ pub fn Vocabulary() -> impl IntoView {
     let (words, set_words) = signal(Vec::new());
     let add_word_form = NodeRef::<leptos::html::Form>::new();
     let show_error = ShowError::from_ctx();
+    let lang = SelectedLanguage::from_ctx();
 
-    // Load words
+    // Load words, again when another language is selected
     Effect::new(move |_| {
-        refresh_words(set_words, show_error);
+        refresh_words(lang.get(), set_words, show_error);
     });
 
     let submit_word_form = ServerAction::<AddWord>::new();
+    let same_stem_words = RwSignal::new(Vec::<String>::new());
     // Handle form submission result
     Effect::new(move |_| {
         if let Some(result) = submit_word_form.value().get() {
             match result {
-                Ok(_) => {
-                    refresh_words(set_words, show_error);
+                Ok(words) => {
+                    same_stem_words.set(words);
+                    refresh_words(lang.get_untracked(), set_words, show_error);
                     if let Some(form) = add_word_form.get() {
                         form.reset();
                     }
@@ -83,6 +98,7 @@ pub fn Vocabulary() -> impl IntoView {
 
     view! {
         <ActionForm action=submit_word_form node_ref=add_word_form>
+            <input type="hidden" name="lang" value=move || lang.get().to_string() />
             <input
                 name="word"
                 type="text"
@@ -115,6 +131,11 @@ pub fn Vocabulary() -> impl IntoView {
                 </svg>
             </button>
         </ActionForm>
+        <Show when=move || !same_stem_words.get().is_empty()>
+            <div class="text-sm text-gray-500">
+                "Similar words, maybe other forms of it: " {move || same_stem_words.get().join(", ")}
+            </div>
+        </Show>
 
         <div class="overflow-x-auto">
             <WordsTable words=words set_words=set_words />
@@ -124,8 +145,8 @@ pub fn Vocabulary() -> impl IntoView {
                     class="bg-blue-500 hover:bg-blue-600 text-white px-4 py-2 rounded"
                     on:click=move |_| {
                         spawn_local(async move {
-                            match add_from_flashcards().await {
-                                Ok(_) => refresh_words(set_words, show_error),
+                            match add_from_flashcards(lang.get_untracked()).await {
+                                Ok(_) => refresh_words(lang.get_untracked(), set_words, show_error),
                                 Err(e) => {
                                     show_error
                                         .show(format!("Failed to add from flashcards:\n {}", e));
@@ -147,6 +168,7 @@ fn WordsTable(
     #[prop(into)] set_words: WriteSignal<Vec<Word>>,
 ) -> impl IntoView {
     let show_error = ShowError::from_ctx();
+    let lang = SelectedLanguage::from_ctx();
     view! {
         <table class="min-w-full bg-white border border-gray-300">
             <thead>
@@ -154,6 +176,7 @@ fn WordsTable(
                     <th class="px-4 py-2 border"></th>
                     <th class="px-4 py-2 border">"Word"</th>
                     <th class="px-4 py-2 border">"Translation"</th>
+                    <th class="px-4 py-2 border">"Familiarity"</th>
                     <th class="px-4 py-2 border">"Created at"</th>
                 </tr>
             </thead>
@@ -175,7 +198,7 @@ fn WordsTable(
                                             viewBox="0 0 24 24"
                                             xmlns="http://www.w3.org/2000/svg"
                                             on:click=move |_| {
-                                                maybe_delete_word(word_text2.clone(), set_words, show_error)
+                                                maybe_delete_word(lang.get_untracked(), word_text2.clone(), set_words, show_error)
                                             }
                                         >
                                             <path
@@ -186,7 +209,12 @@ fn WordsTable(
                                             ></path>
                                         </svg>
                                     </td>
-                                    <td class="px-4 py-2 border">{word_text}</td>
+                                    <td class="px-4 py-2 border">
+                                        {word_text}
+                                        <div class="text-xs text-gray-500">
+                                            {word.forms.join(", ")}
+                                        </div>
+                                    </td>
                                     <td class="px-4 py-2 border">
                                         <input
                                             type="text"
@@ -195,7 +223,11 @@ fn WordsTable(
                                                 let value = event_target_value(&ev);
                                                 let word_text = word.word.clone();
                                                 spawn_local(async move {
-                                                    if let Err(e) = update_word_translation(word_text, value)
+                                                    if let Err(e) = update_word_translation(
+                                                            lang.get_untracked(),
+                                                            word_text,
+                                                            value,
+                                                        )
                                                         .await
                                                     {
                                                         show_error
@@ -207,6 +239,12 @@ fn WordsTable(
                                             }
                                         />
                                     </td>
+                                    <td class="px-4 py-2 border">
+                                        <span class=format!(
+                                            "inline-block rounded-full px-3 py-1 text-sm {}",
+                                            familiarity_class(word.familiarity),
+                                        )>{word.familiarity.as_str()}</span>
+                                    </td>
                                     <td class="px-4 py-2 border">{word.created_at.to_string()}</td>
                                 </tr>
                             }
@@ -218,7 +256,12 @@ fn WordsTable(
     }
 }
 
-fn maybe_delete_word(word_text: String, set_words: WriteSignal<Vec<Word>>, show_error: ShowError) {
+fn maybe_delete_word(
+    lang: Language,
+    word_text: String,
+    set_words: WriteSignal<Vec<Word>>,
+    show_error: ShowError,
+) {
     if let Some(window) = web_sys::window() {
         if let Ok(confirmed) = window
             .confirm_with_message(&format!("Are you sure you want to delete '{}'?", word_text,))
@@ -226,8 +269,8 @@ fn maybe_delete_word(word_text: String, set_words: WriteSignal<Vec<Word>>, show_
             if confirmed {
                 let word_to_delete = word_text.clone();
                 spawn_local(async move {
-                    match delete_word(word_to_delete).await {
-                        Ok(_) => refresh_words(set_words, show_error),
+                    match delete_word(lang.clone(), word_to_delete).await {
+                        Ok(_) => refresh_words(lang, set_words, show_error),
                         Err(e) => {
                             show_error.show(format!("Failed to delete word:\n {}", e));
                         }
@@ -237,3 +280,11 @@ fn maybe_delete_word(word_text: String, set_words: WriteSignal<Vec<Word>>, show_
         }
     }
 }
+
+fn familiarity_class(familiarity: Familiarity) -> &'static str {
+    match familiarity {
+        Familiarity::New => "bg-slate-100 text-slate-600",
+        Familiarity::Learning => "bg-amber-100 text-amber-700",
+        Familiarity::Known => "bg-green-100 text-green-700",
+    }
+}
@@ -0,0 +1,198 @@
+//! Conjugation drills: flashcards like "hablar, presente, nosotros → ?" with the verb form
+//! as the answer, reviewed like any other card. Conjugation tables come from an imported
+//! data set or the LLM, and are kept in the words database.
+
+#![cfg(feature = "ssr")]
+
+use std::collections::HashSet;
+
+use crate::db::Database as FlashcardsDb;
+use crate::errors::AppError;
+use crate::languages::ai::Agent;
+use crate::languages::model::{Conjugation, CONJUGATION_TAG};
+use crate::languages::morphology::Morphology;
+use crate::model::Flashcard;
+use crate::settings::{Language, LanguageInfo};
+use crate::words_db;
+
+/// Checks that the table has one form, and nothing else, for each person of the language.
+pub fn validate(conjugation: &Conjugation, lang: &LanguageInfo) -> Result<(), String> {
+    let persons = conjugation
+        .forms
+        .iter()
+        .map(|(person, _)| person)
+        .collect::<Vec<_>>();
+    if persons != lang.persons.iter().collect::<Vec<_>>() {
+        return Err(format!(
+            "Expected the persons {:?}, got {:?}",
+            lang.persons, persons
+        ));
+    }
+    for (person, form) in conjugation.forms.iter() {
+        // Compound tenses have an auxiliary verb, e.g. "he hablado".
+        let is_form = !form.is_empty()
+            && form.split_whitespace().count() <= 3
+            && form
+                .chars()
+                .all(|c| c.is_alphabetic() || c == ' ' || c == '-' || c == '\'' || c == '’');
+        if !is_form {
+            return Err(format!("Invalid form for '{}': {:?}", person, form));
+        }
+    }
+    Ok(())
+}
+
+/// The conjugation table from the words database, or from the LLM, then it's saved.
+pub async fn conjugate(agent: &Agent, verb: &str, tense: &str) -> Result<Conjugation, AppError> {
+    let info = agent.lang.info()?;
+    if !info.tenses.iter().any(|t| t == tense) {
+        return Err(AppError::InvalidInput(format!(
+            "{} has no tense '{}'",
+            info.name, tense
+        )));
+    }
+    let verb = Morphology::normalize(verb);
+
+    let mut forms = words_db!(agent.lang).conjugation(&verb, tense)?;
+    if !forms.is_empty() {
+        forms.sort_by_key(|(person, _)| info.persons.iter().position(|p| p == person));
+        return Ok(Conjugation {
+            verb,
+            tense: tense.to_string(),
+            forms,
+        });
+    }
+
+    let conjugation = agent.conjugate(&verb, tense).await?;
+    words_db!(agent.lang).save_conjugation(&conjugation)?;
+    Ok(conjugation)
+}
+
+/// Tag of the drills of a tense, e.g. "spanish-pretérito-indefinido".
+pub fn tense_tag(lang: &Language, tense: &str) -> String {
+    format!(
+        "{}-{}",
+        lang,
+        tense
+            .to_lowercase()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join("-")
+    )
+}
+
+/// A card per person, tagged with the language, "conjugation" and the tense.
+pub fn drill_cards(conjugation: &Conjugation, lang: &Language) -> Vec<Flashcard> {
+    conjugation
+        .forms
+        .iter()
+        .map(|(person, form)| {
+            let mut card = Flashcard::new(
+                format!(
+                    "{}, {}, {} → ?",
+                    conjugation.verb, conjugation.tense, person
+                ),
+                form.clone(),
+            );
+            card.tags = vec![
+                lang.to_string(),
+                CONJUGATION_TAG.to_string(),
+                tense_tag(lang, &conjugation.tense),
+            ];
+            card.source = Some("learning-languages app".to_string());
+            card
+        })
+        .collect()
+}
+
+/// Adds the drills that don't exist yet. Returns how many were added.
+pub fn save_drills(conjugation: &Conjugation, lang: &Language) -> Result<usize, AppError> {
+    let db = FlashcardsDb::get_instance().unwrap().lock().unwrap();
+    let existing = db
+        .all_cards(Some(tense_tag(lang, &conjugation.tense)))?
+        .into_iter()
+        .map(|card| card.question)
+        .collect::<HashSet<_>>();
+    let mut count = 0;
+    for card in drill_cards(conjugation, lang) {
+        if !existing.contains(&card.question) {
+            db.add_card(&card)
+                .map_err(|e| AppError::DuckdbError(e.to_string()))?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Parses a conjugation data set with a `verb<TAB>tense<TAB>person<TAB>form` per line.
+/// Empty lines and lines starting with `#` are skipped.
+pub fn parse_conjugations(text: &str) -> Vec<Conjugation> {
+    let mut conjugations: Vec<Conjugation> = Vec::new();
+    let lines = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+    for line in lines {
+        let [verb, tense, person, form] = line.split('\t').map(str::trim).collect::<Vec<_>>()[..]
+        else {
+            continue;
+        };
+        let verb = verb.to_lowercase();
+        let form = (person.to_string(), form.to_string());
+        match conjugations
+            .iter_mut()
+            .find(|c| c.verb == verb && c.tense == tense)
+        {
+            Some(conjugation) => conjugation.forms.push(form),
+            None => conjugations.push(Conjugation {
+                verb,
+                tense: tense.to_string(),
+                forms: vec![form],
+            }),
+        }
+    }
+    conjugations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::languages::model::is_conjugation_drill;
+
+    #[test]
+    fn test_conjugation_drills() {
+        let spanish = Language::new("spanish");
+        let info = spanish.info().unwrap();
+        let mut conjugations = parse_conjugations(
+            "# verb\ttense\tperson\tform\n\
+             hablar\tpresente\tyo\thablo\n\
+             hablar\tpresente\ttú\thablas\n\
+             hablar\tpresente\tél\thabla\n\
+             hablar\tpresente\tnosotros\thablamos\n\
+             hablar\tpresente\tvosotros\thabláis\n\
+             hablar\tpresente\tellos\thablan\n\
+             ser\tpresente\tyo\tsoy\n",
+        );
+        assert_eq!(conjugations.len(), 2);
+        assert!(validate(&conjugations[0], &info).is_ok());
+        assert!(validate(&conjugations[1], &info).is_err());
+
+        conjugations[0].forms[0].1 = "<hablo>".to_string();
+        assert!(validate(&conjugations[0], &info).is_err());
+        conjugations[0].forms[0].1 = "hablo".to_string();
+
+        let cards = drill_cards(&conjugations[0], &spanish);
+        assert_eq!(cards.len(), 6);
+        assert_eq!(cards[3].question, "hablar, presente, nosotros → ?");
+        assert_eq!(cards[3].answer, "hablamos");
+        assert!(cards.iter().all(is_conjugation_drill));
+        assert_eq!(
+            cards[3].tags,
+            vec!["spanish", "conjugation", "spanish-presente"]
+        );
+        assert_eq!(
+            tense_tag(&spanish, "Presente de subjuntivo"),
+            "spanish-presente-de-subjuntivo"
+        );
+    }
+}
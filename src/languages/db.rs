@@ -1,82 +1,351 @@
 #![cfg(feature = "ssr")]
 
-use duckdb::{params, types::Value, Connection, Error as DuckdbError, Result};
-use once_cell::sync::OnceCell;
-use std::sync::Mutex;
+use duckdb::{params, params_from_iter, types::Value, Connection, Error as DuckdbError, Result};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
 
 use crate::db::from_duckdb_timestamp;
-use crate::languages::model::Word;
+use crate::errors::AppError;
+use crate::languages::model::{
+    familiar_words, Conjugation, Coverage, Familiarity, ListeningReview, NewSentence,
+    SavedSentence, SavedStory, Word,
+};
+use crate::languages::morphology::{tokenize, Morphology};
+use crate::model::{Flashcard, Grade};
+use crate::settings::{Language, LanguageInfo};
 
 static INIT_TABLES_SQL: &str = "
+-- Words are stored in their dictionary form (lemma).
 CREATE TABLE IF NOT EXISTS words (
     word TEXT NOT NULL PRIMARY KEY,
     translation TEXT,
     created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
 );
+ALTER TABLE words ADD COLUMN IF NOT EXISTS stem TEXT;
+ALTER TABLE words ADD COLUMN IF NOT EXISTS familiarity TEXT DEFAULT 'new';
+-- Reviews remembered in a row.
+ALTER TABLE words ADD COLUMN IF NOT EXISTS streak INTEGER DEFAULT 0;
+ALTER TABLE words ADD COLUMN IF NOT EXISTS last_seen DATETIME;
+-- Inflected forms of the words that were seen, e.g. 'hablaba' for 'hablar'.
+CREATE TABLE IF NOT EXISTS word_forms (
+    form TEXT NOT NULL PRIMARY KEY,
+    word TEXT NOT NULL,
+);
+-- Imported lemma list: the dictionary form of inflected words.
+CREATE TABLE IF NOT EXISTS lemmas (
+    form TEXT NOT NULL PRIMARY KEY,
+    lemma TEXT NOT NULL,
+);
+-- Imported bilingual dictionary, a word can have multiple translations.
+CREATE TABLE IF NOT EXISTS dictionary (
+    word TEXT NOT NULL,
+    translation TEXT NOT NULL,
+);
+CREATE INDEX IF NOT EXISTS dictionary_word_idx ON dictionary (word);
+-- Translations from online translators, so that they are requested only once.
+-- Cached per target language, which can be changed in the settings.
+CREATE TABLE IF NOT EXISTS translation_cache (
+    text TEXT NOT NULL,
+    target_language TEXT NOT NULL,
+    translation TEXT NOT NULL,
+    translator TEXT NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (text, target_language),
+);
+-- Generated stories and sentences, with the share of known words when they were generated.
+CREATE SEQUENCE IF NOT EXISTS seq_stories;
+CREATE TABLE IF NOT EXISTS stories (
+    id INTEGER PRIMARY KEY DEFAULT NEXTVAL('seq_stories'),
+    text TEXT NOT NULL,
+    known_ratio DOUBLE NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+);
+-- Stories have no title, texts imported to read them have one.
+ALTER TABLE stories ADD COLUMN IF NOT EXISTS title TEXT;
+-- Chapters of a book are saved together, numbered from 1 in the reading order.
+ALTER TABLE stories ADD COLUMN IF NOT EXISTS chapter INTEGER;
+-- Words looked up while reading a story.
+CREATE TABLE IF NOT EXISTS story_lookups (
+    story_id INTEGER NOT NULL,
+    word TEXT NOT NULL,
+    PRIMARY KEY (story_id, word),
+);
+CREATE SEQUENCE IF NOT EXISTS seq_generated_sentences;
+CREATE TABLE IF NOT EXISTS generated_sentences (
+    id INTEGER PRIMARY KEY DEFAULT NEXTVAL('seq_generated_sentences'),
+    text TEXT NOT NULL,
+    new_word TEXT NOT NULL,
+    translation TEXT NOT NULL,
+    known_ratio DOUBLE NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+);
+-- Conjugated verbs, generated by the LLM or imported: a form per tense and person.
+CREATE TABLE IF NOT EXISTS conjugations (
+    verb TEXT NOT NULL,
+    tense TEXT NOT NULL,
+    person TEXT NOT NULL,
+    form TEXT NOT NULL,
+    PRIMARY KEY (verb, tense, person),
+);
+-- Listening reviews of sentence cards, scheduled apart from the reading reviews
+-- in the flashcards database.
+CREATE TABLE IF NOT EXISTS listening_schedule (
+    flashcard_id INTEGER NOT NULL PRIMARY KEY,
+    last_reviewed DATETIME NOT NULL,
+    review_after_secs INTEGER NOT NULL,
+);
+CREATE TABLE IF NOT EXISTS listening_history (
+    flashcard_id INTEGER NOT NULL,
+    review_date DATETIME NOT NULL,
+    score DOUBLE NOT NULL,
+    grade TEXT NOT NULL,
+);
 ";
 
+/// Locks the words database of the given language, or of `learning_language` from the settings.
 #[macro_export]
 macro_rules! words_db {
     () => {
-        crate::languages::db::Database::get_instance(
-            &crate::settings::Settings::get().db_path,
-            crate::settings::Settings::get().learning_language.as_str(),
-        )
-        .unwrap()
+        $crate::words_db!($crate::settings::Settings::get().learning_language)
+    };
+    ($lang:expr) => {
+        $crate::languages::db::Database::get_instance(
+            &$crate::settings::Settings::get().db_path,
+            &$lang,
+        )?
         .lock()
         .unwrap()
     };
 }
 
-static DATABASE: OnceCell<Mutex<Database>> = OnceCell::new();
+/// Reviews remembered in a row after which a word is known.
+const KNOWN_STREAK: i32 = 3;
+
+/// One database per directory and language, opened the first time it's used.
+type Databases = HashMap<(String, Language), &'static Mutex<Database>>;
+static DATABASES: OnceLock<Mutex<Databases>> = OnceLock::new();
 
 pub struct Database {
     conn: Connection,
+    morphology: Morphology,
 }
 
 impl Database {
+    /// Unknown languages are rejected before anything is created in `db_path`.
     pub fn get_instance(
         db_path: &str,
-        lang: &str,
-    ) -> Result<&'static Mutex<Database>, DuckdbError> {
-        DATABASE.get_or_try_init(|| {
-            let db = Database::load_or_init(&format!("{}/{}.db", db_path, lang))?;
-            Ok(Mutex::new(db))
-        })
+        lang: &Language,
+    ) -> Result<&'static Mutex<Database>, AppError> {
+        let mut databases = DATABASES
+            .get_or_init(|| Mutex::new(HashMap::new()))
+            .lock()
+            .unwrap();
+        let key = (db_path.to_string(), lang.clone());
+        if let Some(db) = databases.get(&key).copied() {
+            return Ok(db);
+        }
+        let info = lang.info()?;
+        let mut db = Database::load_or_init(&format!("{}/{}.db", db_path, lang.as_str()))?;
+        db.set_morphology(Morphology::new(&info))?;
+        // Leaked on purpose: there are only a few languages and they live as long as the app.
+        let db: &'static Mutex<Database> = Box::leak(Box::new(Mutex::new(db)));
+        databases.insert(key, db);
+        Ok(db)
     }
 
     #[cfg(test)]
-    fn in_memory() -> Result<Self, DuckdbError> {
+    pub fn in_memory() -> Result<Self, DuckdbError> {
         let conn = Connection::open_in_memory()?;
         conn.execute_batch(INIT_TABLES_SQL)?;
-        Ok(Self { conn })
+        Ok(Self {
+            conn,
+            morphology: Morphology::default(),
+        })
     }
 
     // Load existing db or create a new one if it doesn't exist.
     pub fn load_or_init(fname: &str) -> Result<Self, DuckdbError> {
         let conn = Connection::open(fname)?;
         conn.execute_batch(INIT_TABLES_SQL)?;
-        Ok(Self { conn })
+        Ok(Self {
+            conn,
+            morphology: Morphology::default(),
+        })
     }
 
-    // Idempotent.
+    /// Also stems the words that were added without a stem, e.g. before stemming existed.
+    pub fn set_morphology(&mut self, morphology: Morphology) -> Result<(), DuckdbError> {
+        self.morphology = morphology;
+        let mut stmt = self
+            .conn
+            .prepare("SELECT word FROM words WHERE stem IS NULL")?;
+        let words = stmt
+            .query_map(params![], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        for word in words {
+            self.conn.execute(
+                "UPDATE words SET stem = ? WHERE word = ?",
+                params![self.morphology.stem(&word), word],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// The dictionary form of the word from the lemma list, or the word itself.
+    pub fn lemma(&self, word: &str) -> Result<String, DuckdbError> {
+        let word = Morphology::normalize(word);
+        let mut stmt = self
+            .conn
+            .prepare("SELECT lemma FROM lemmas WHERE form = ?")?;
+        let lemma = stmt
+            .query_map(params![word], |row| row.get::<_, String>(0))?
+            .next()
+            .transpose()?;
+        Ok(lemma.unwrap_or(word))
+    }
+
+    /// Adds (form, lemma) pairs to the lemma list. Returns how many were added.
+    /// A single transaction: lemma lists have hundreds of thousands of lines.
+    pub fn import_lemmas(
+        &self,
+        entries: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<usize, DuckdbError> {
+        self.conn.execute_batch("BEGIN TRANSACTION")?;
+        let count = (|| -> Result<usize, DuckdbError> {
+            let mut stmt = self
+                .conn
+                .prepare("INSERT INTO lemmas (form, lemma) VALUES (?, ?) ON CONFLICT DO NOTHING")?;
+            let mut count = 0;
+            for (form, lemma) in entries {
+                count += stmt.execute(params![form, lemma])?;
+            }
+            Ok(count)
+        })();
+        self.conn
+            .execute_batch(if count.is_ok() { "COMMIT" } else { "ROLLBACK" })?;
+        count
+    }
+
+    /// Adds the word in its dictionary form and remembers the given form. Idempotent.
     pub fn add_word(&self, word: &str, translation: &str) -> Result<(), DuckdbError> {
+        let form = Morphology::normalize(word);
+        let lemma = self.lemma(&form)?;
         self.conn.execute(
-            "INSERT INTO words (word, translation) VALUES (?, ?) ON CONFLICT DO NOTHING",
-            params![word, translation],
+            "INSERT INTO words (word, translation, stem) VALUES (?, ?, ?) ON CONFLICT DO NOTHING",
+            params![lemma, translation, self.morphology.stem(&lemma)],
         )?;
+        if form != lemma {
+            self.conn.execute(
+                "INSERT INTO word_forms (form, word) VALUES (?, ?) ON CONFLICT DO NOTHING",
+                params![form, lemma],
+            )?;
+        }
         Ok(())
     }
 
+    /// Adds the (word, translation) pairs that are not in my vocabulary yet, all of them or
+    /// none. Returns the added words.
+    pub fn add_new_words(&self, words: &[(String, String)]) -> Result<Vec<String>, DuckdbError> {
+        self.conn.execute_batch("BEGIN TRANSACTION")?;
+        let added = (|| -> Result<Vec<String>, DuckdbError> {
+            let mut added = Vec::new();
+            for (word, translation) in words {
+                if self.find_word(word)?.is_none() {
+                    self.add_word(word, translation)?;
+                    added.push(word.clone());
+                }
+            }
+            Ok(added)
+        })();
+        self.conn
+            .execute_batch(if added.is_ok() { "COMMIT" } else { "ROLLBACK" })?;
+        added
+    }
+
+    /// Finds my word for any of its forms: the word itself, its lemma or a form seen with it.
+    /// The same stem isn't enough, different words share stems too, see [Self::same_stem_words].
+    pub fn find_word(&self, form: &str) -> Result<Option<String>, DuckdbError> {
+        let form = Morphology::normalize(form);
+        let lemma = self.lemma(&form)?;
+        let mut stmt = self.conn.prepare(
+            "SELECT word FROM (
+                SELECT word, 1 AS priority FROM words WHERE word = ? OR word = ?
+                UNION ALL
+                SELECT word, 2 AS priority FROM word_forms WHERE form = ?
+            )
+            ORDER BY priority, word
+            LIMIT 1",
+        )?;
+        let mut rows = stmt.query_map(params![form, lemma, form], |row| row.get::<_, String>(0))?;
+        rows.next().transpose()
+    }
+
+    /// My words for the tokens of a text, matched like in [Self::find_word] but in a single
+    /// query: a book chapter has thousands of tokens. Tokens have no spaces, see [tokenize].
+    fn find_token_words(&self, tokens: &[&String]) -> Result<HashMap<String, String>, DuckdbError> {
+        let tokens = tokens
+            .iter()
+            .map(|token| token.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut stmt = self.conn.prepare(
+            "WITH tokens AS (SELECT DISTINCT unnest(string_split(?, ' ')) AS form)
+            SELECT form, word FROM (
+                SELECT t.form, w.word, 1 AS priority FROM tokens t
+                LEFT JOIN lemmas l ON l.form = t.form
+                JOIN words w ON w.word = t.form OR w.word = l.lemma
+                UNION ALL
+                SELECT t.form, f.word, 2 AS priority FROM tokens t
+                JOIN word_forms f ON f.form = t.form
+            )
+            ORDER BY form, priority, word",
+        )?;
+        let rows = stmt.query_map(params![tokens], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut words = HashMap::new();
+        for row in rows {
+            let (form, word) = row?;
+            words.entry(form).or_insert(word);
+        }
+        Ok(words)
+    }
+
+    /// My other words with the same stem as the form. They may be its other forms or different
+    /// words, e.g. "caso" for "casa", so they are only suggestions.
+    pub fn same_stem_words(&self, form: &str) -> Result<Vec<String>, DuckdbError> {
+        let form = Morphology::normalize(form);
+        let lemma = self.lemma(&form)?;
+        let mut stmt = self.conn.prepare(
+            "SELECT word FROM words WHERE stem = ? AND word <> ? AND word <> ? ORDER BY word",
+        )?;
+        let words = stmt.query_map(params![self.morphology.stem(&lemma), form, lemma], |row| {
+            row.get::<_, String>(0)
+        })?;
+        words.collect()
+    }
+
     pub fn all_words(&self) -> Result<Vec<Word>, DuckdbError> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT word, translation, created_at FROM words")?;
+        let mut stmt = self.conn.prepare(
+            "SELECT w.word, w.translation, w.created_at, string_agg(f.form, ',' ORDER BY f.form),
+                w.familiarity
+            FROM words w
+            LEFT JOIN word_forms f ON f.word = w.word
+            GROUP BY w.word, w.translation, w.created_at, w.familiarity",
+        )?;
         let words = stmt.query_map(params![], |row| {
             Ok(Word {
                 word: row.get(0)?,
                 translation: row.get(1)?,
                 created_at: from_duckdb_timestamp(row.get::<_, Value>(2)?),
+                forms: row
+                    .get::<_, Option<String>>(3)?
+                    .map(|forms| forms.split(',').map(String::from).collect())
+                    .unwrap_or_default(),
+                familiarity: row
+                    .get::<_, Option<String>>(4)?
+                    .and_then(|familiarity| familiarity.parse().ok())
+                    .unwrap_or_default(),
             })
         })?;
         Ok(words.collect::<Result<Vec<Word>, _>>()?)
@@ -95,12 +364,341 @@ impl Database {
     }
 
     pub fn delete_word(&self, word: &str) -> Result<(), DuckdbError> {
+        self.conn
+            .execute("DELETE FROM word_forms WHERE word = ?", params![word])?;
         self.conn
             .execute("DELETE FROM words WHERE word = ?", params![word])?;
         Ok(())
     }
 
+    /// Updates the familiarity of my word, given in any of its forms, after a review of a sentence
+    /// with it. Unknown words are ignored.
+    pub fn record_review(&self, word: &str, remembered: bool) -> Result<(), DuckdbError> {
+        let Some(word) = self.find_word(word)? else {
+            return Ok(());
+        };
+        if remembered {
+            self.conn.execute(
+                "UPDATE words SET
+                    streak = streak + 1,
+                    familiarity = CASE WHEN streak + 1 >= ? THEN ? ELSE ? END,
+                    last_seen = CURRENT_TIMESTAMP
+                WHERE word = ?",
+                params![
+                    KNOWN_STREAK,
+                    Familiarity::Known.as_str(),
+                    Familiarity::Learning.as_str(),
+                    word
+                ],
+            )?;
+        } else {
+            self.set_learning(&word)?;
+        }
+        Ok(())
+    }
+
+    /// Reviews each of my words in the sentence once, even if it's there in several forms.
+    pub fn record_sentence_review(
+        &self,
+        sentence: &str,
+        remembered: bool,
+    ) -> Result<(), DuckdbError> {
+        let mut words = BTreeSet::new();
+        for form in tokenize(sentence) {
+            if let Some(word) = self.find_word(&form)? {
+                words.insert(word);
+            }
+        }
+        for word in words {
+            self.record_review(&word, remembered)?;
+        }
+        Ok(())
+    }
+
+    /// Looking up a word means I don't know it well.
+    pub fn record_lookup(&self, word: &str) -> Result<(), DuckdbError> {
+        if let Some(word) = self.find_word(word)? {
+            self.set_learning(&word)?;
+        }
+        Ok(())
+    }
+
+    fn set_learning(&self, word: &str) -> Result<(), DuckdbError> {
+        self.conn.execute(
+            "UPDATE words SET streak = 0, familiarity = ?, last_seen = CURRENT_TIMESTAMP
+            WHERE word = ?",
+            params![Familiarity::Learning.as_str(), word],
+        )?;
+        Ok(())
+    }
+
+    /// How much of the text is in my vocabulary.
+    pub fn coverage(&self, text: &str, lang: &LanguageInfo) -> Result<Coverage, DuckdbError> {
+        let tokens = tokenize(text);
+        let words = self.all_words()?;
+        let familiar = familiar_words(&words)
+            .into_iter()
+            .map(|word| word.word.as_str())
+            .collect::<HashSet<_>>();
+        // Long texts repeat the same words a lot.
+        let unique_tokens = tokens
+            .iter()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let token_words = self.find_token_words(&unique_tokens)?;
+        let mut unknown_words = BTreeSet::new();
+        for token in unique_tokens {
+            let known = lang.is_skipped_word(token)
+                || token.chars().all(|c| c.is_numeric())
+                || token_words
+                    .get(token)
+                    .is_some_and(|word| familiar.contains(word.as_str()));
+            if !known {
+                unknown_words.insert(token.clone());
+            }
+        }
+        let unknown_count = tokens
+            .iter()
+            .filter(|token| unknown_words.contains(*token))
+            .count();
+        Ok(Coverage {
+            known_ratio: if tokens.is_empty() {
+                1.0
+            } else {
+                1.0 - unknown_count as f64 / tokens.len() as f64
+            },
+            unknown_words: unknown_words.into_iter().collect(),
+        })
+    }
+
+    /// Keeps the story, or the imported text, in the library. Returns its id.
+    pub fn save_story(
+        &self,
+        title: Option<&str>,
+        text: &str,
+        known_ratio: f64,
+    ) -> Result<i64, DuckdbError> {
+        self.conn.query_row(
+            "INSERT INTO stories (title, text, known_ratio) VALUES (?, ?, ?) RETURNING id",
+            params![title, text, known_ratio],
+            |row| row.get(0),
+        )
+    }
+
+    /// Saves the (chapter, known_ratio) of a book at once, titled "title, i/n".
+    /// Returns their ids in the reading order.
+    pub fn save_book(
+        &self,
+        title: &str,
+        chapters: &[(String, f64)],
+    ) -> Result<Vec<i64>, DuckdbError> {
+        self.conn.execute_batch("BEGIN TRANSACTION")?;
+        let ids = (|| -> Result<Vec<i64>, DuckdbError> {
+            let mut stmt = self.conn.prepare(
+                "INSERT INTO stories (title, text, known_ratio, chapter) VALUES (?, ?, ?, ?)
+                RETURNING id",
+            )?;
+            let mut ids = Vec::new();
+            for (i, (text, known_ratio)) in chapters.iter().enumerate() {
+                let chapter_title = format!("{}, {}/{}", title, i + 1, chapters.len());
+                ids.push(stmt.query_row(
+                    params![chapter_title, text, known_ratio, i as i64 + 1],
+                    |row| row.get(0),
+                )?);
+            }
+            Ok(ids)
+        })();
+        self.conn
+            .execute_batch(if ids.is_ok() { "COMMIT" } else { "ROLLBACK" })?;
+        ids
+    }
+
+    /// Stories from the newest, `None` for all of them.
+    /// The chapters of a book share the time of the transaction, they're in the reading order.
+    fn query_stories(&self, id: Option<i64>) -> Result<Vec<SavedStory>, DuckdbError> {
+        let filter = if id.is_some() { "WHERE s.id = ?" } else { "" };
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT s.id, s.text, s.known_ratio, s.created_at, string_agg(l.word, ',' ORDER BY l.word),
+                s.title
+            FROM stories s
+            LEFT JOIN story_lookups l ON l.story_id = s.id
+            {}
+            GROUP BY s.id, s.text, s.known_ratio, s.created_at, s.title, s.chapter
+            ORDER BY s.created_at DESC, s.chapter, s.id DESC",
+            filter
+        ))?;
+        let stories = stmt.query_map(params_from_iter(id), |row| {
+            Ok(SavedStory {
+                id: row.get(0)?,
+                text: row.get(1)?,
+                known_ratio: row.get(2)?,
+                created_at: from_duckdb_timestamp(row.get::<_, Value>(3)?),
+                looked_up: row
+                    .get::<_, Option<String>>(4)?
+                    .map(|words| words.split(',').map(String::from).collect())
+                    .unwrap_or_default(),
+                title: row.get(5)?,
+            })
+        })?;
+        stories.collect()
+    }
+
+    pub fn all_stories(&self) -> Result<Vec<SavedStory>, DuckdbError> {
+        self.query_stories(None)
+    }
+
+    pub fn get_story(&self, id: i64) -> Result<Option<SavedStory>, DuckdbError> {
+        Ok(self.query_stories(Some(id))?.into_iter().next())
+    }
+
+    pub fn record_story_lookup(&self, story_id: i64, word: &str) -> Result<(), DuckdbError> {
+        self.conn.execute(
+            "INSERT INTO story_lookups (story_id, word) VALUES (?, ?) ON CONFLICT DO NOTHING",
+            params![story_id, Morphology::normalize(word)],
+        )?;
+        Ok(())
+    }
+
+    /// Keeps the generated sentence in the library. Returns its id.
+    pub fn save_generated_sentence(
+        &self,
+        sentence: &NewSentence,
+        known_ratio: f64,
+    ) -> Result<i64, DuckdbError> {
+        self.conn.query_row(
+            "INSERT INTO generated_sentences (text, new_word, translation, known_ratio)
+            VALUES (?, ?, ?, ?) RETURNING id",
+            params![
+                sentence.text,
+                sentence.new_word,
+                sentence.translation,
+                known_ratio
+            ],
+            |row| row.get(0),
+        )
+    }
+
+    /// Generated sentences from the newest.
+    pub fn all_generated_sentences(&self) -> Result<Vec<SavedSentence>, DuckdbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, text, new_word, translation, known_ratio, created_at
+            FROM generated_sentences
+            ORDER BY created_at DESC, id DESC",
+        )?;
+        let sentences = stmt.query_map(params![], |row| {
+            Ok(SavedSentence {
+                id: row.get(0)?,
+                sentence: NewSentence {
+                    text: row.get(1)?,
+                    new_word: row.get(2)?,
+                    translation: row.get(3)?,
+                },
+                known_ratio: row.get(4)?,
+                created_at: from_duckdb_timestamp(row.get::<_, Value>(5)?),
+            })
+        })?;
+        sentences.collect()
+    }
+
+    /// Forms of the verb in the tense, as (person, form) pairs, empty when it's unknown.
+    pub fn conjugation(
+        &self,
+        verb: &str,
+        tense: &str,
+    ) -> Result<Vec<(String, String)>, DuckdbError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT person, form FROM conjugations WHERE verb = ? AND tense = ?")?;
+        let forms = stmt.query_map(params![verb, tense], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        forms.collect()
+    }
+
+    pub fn save_conjugation(&self, conjugation: &Conjugation) -> Result<(), DuckdbError> {
+        for (person, form) in conjugation.forms.iter() {
+            self.conn.execute(
+                "INSERT OR REPLACE INTO conjugations (verb, tense, person, form) VALUES (?, ?, ?, ?)",
+                params![conjugation.verb, conjugation.tense, person, form],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Cards listened to that are not due yet. Cards never listened to are due.
+    pub fn listened_recently(&self) -> Result<HashSet<i64>, DuckdbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT flashcard_id FROM listening_schedule
+            WHERE last_reviewed + INTERVAL(review_after_secs) SECOND >= CURRENT_TIMESTAMP",
+        )?;
+        let ids = stmt.query_map([], |row| row.get(0))?;
+        ids.collect()
+    }
+
+    /// Reschedules the listening review of the card and records it.
+    pub fn record_listening(
+        &self,
+        card_id: i64,
+        score: f64,
+        grade: Grade,
+    ) -> Result<ListeningReview, DuckdbError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT review_after_secs FROM listening_schedule WHERE flashcard_id = ?")?;
+        let review_after_secs = stmt
+            .query_map(params![card_id], |row| row.get::<_, i64>(0))?
+            .next()
+            .transpose()?
+            // The first listening is scheduled like a new card.
+            .unwrap_or(Flashcard::new(String::new(), String::new()).review_after_secs);
+
+        self.conn.execute_batch("BEGIN TRANSACTION")?;
+        let review = (|| -> Result<ListeningReview, DuckdbError> {
+            self.conn.execute(
+                "INSERT OR REPLACE INTO listening_schedule (flashcard_id, last_reviewed, review_after_secs)
+                VALUES (?, CURRENT_TIMESTAMP, ?)",
+                params![card_id, grade.next_review_after_secs(review_after_secs)],
+            )?;
+            self.conn.query_row(
+                "INSERT INTO listening_history (flashcard_id, review_date, score, grade)
+                VALUES (?, CURRENT_TIMESTAMP, ?, ?) RETURNING review_date",
+                params![card_id, score, grade.as_str()],
+                |row| {
+                    Ok(ListeningReview {
+                        flashcard_id: card_id,
+                        review_date: from_duckdb_timestamp(row.get::<_, Value>(0)?),
+                        score,
+                        grade,
+                    })
+                },
+            )
+        })();
+        self.conn
+            .execute_batch(if review.is_ok() { "COMMIT" } else { "ROLLBACK" })?;
+        review
+    }
+
+    /// Listening reviews from the oldest.
+    pub fn listening_history(&self) -> Result<Vec<ListeningReview>, DuckdbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT flashcard_id, review_date, score, grade FROM listening_history
+            ORDER BY review_date",
+        )?;
+        let reviews = stmt.query_map([], |row| {
+            Ok(ListeningReview {
+                flashcard_id: row.get(0)?,
+                review_date: from_duckdb_timestamp(row.get::<_, Value>(1)?),
+                score: row.get(2)?,
+                grade: row.get::<_, String>(3)?.parse().unwrap_or(Grade::Again),
+            })
+        })?;
+        reviews.collect()
+    }
+
+    /// Translation of my word, given in any of its forms.
     pub fn get_translation(&self, word: &str) -> Result<Option<String>, DuckdbError> {
+        let Some(word) = self.find_word(word)? else {
+            return Ok(None);
+        };
         let mut stmt = self
             .conn
             .prepare("SELECT translation FROM words WHERE word = ?")?;
@@ -112,4 +710,305 @@ impl Database {
             Ok(None)
         }
     }
+
+    /// Replaces the dictionary with the (word, translation) pairs, so that importing it again
+    /// doesn't repeat the translations. Returns how many were added.
+    pub fn import_dictionary(
+        &self,
+        entries: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<usize, DuckdbError> {
+        self.conn.execute_batch("BEGIN TRANSACTION")?;
+        let count = (|| -> Result<usize, DuckdbError> {
+            self.conn.execute("DELETE FROM dictionary", params![])?;
+            let mut appender = self.conn.appender("dictionary")?;
+            let mut count = 0;
+            for (word, translation) in entries {
+                appender.append_row(params![word, translation])?;
+                count += 1;
+            }
+            appender.flush()?;
+            Ok(count)
+        })();
+        self.conn
+            .execute_batch(if count.is_ok() { "COMMIT" } else { "ROLLBACK" })?;
+        count
+    }
+
+    /// All the dictionary translations of the word, separated by "; ". Inflected forms are
+    /// looked up in their dictionary form when the dictionary doesn't have them.
+    pub fn dictionary_lookup(&self, word: &str) -> Result<Option<String>, DuckdbError> {
+        let form = Morphology::normalize(word);
+        let lemma = self.lemma(&form)?;
+        for word in [word.trim().to_lowercase(), form, lemma] {
+            let translations = self.conn.query_row(
+                "SELECT string_agg(translation, '; ' ORDER BY rowid) FROM dictionary WHERE word = ?",
+                params![word],
+                |row| row.get::<_, Option<String>>(0),
+            )?;
+            if translations.is_some() {
+                return Ok(translations);
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn cached_translation(
+        &self,
+        text: &str,
+        target_language: &str,
+    ) -> Result<Option<String>, DuckdbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT translation FROM translation_cache WHERE text = ? AND target_language = ?",
+        )?;
+        let mut rows = stmt.query_map(params![text, target_language], |row| {
+            row.get::<_, String>(0)
+        })?;
+        rows.next().transpose()
+    }
+
+    pub fn cache_translation(
+        &self,
+        text: &str,
+        target_language: &str,
+        translation: &str,
+        translator: &str,
+    ) -> Result<(), DuckdbError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO translation_cache (text, target_language, translation, translator)
+            VALUES (?, ?, ?, ?)",
+            params![text, target_language, translation, translator],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_each_language_has_its_own_db() {
+        let dir = std::env::temp_dir().join(format!("words-db-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_file = |lang: &str| {
+            dir.join(format!("{}.db", lang))
+                .to_str()
+                .unwrap()
+                .to_string()
+        };
+
+        {
+            let spanish = Database::load_or_init(&db_file("spanish")).unwrap();
+            let french = Database::load_or_init(&db_file("french")).unwrap();
+            spanish.add_word("hola", "hello").unwrap();
+            assert_eq!(spanish.all_words().unwrap().len(), 1);
+            assert!(french.all_words().unwrap().is_empty());
+        }
+        let spanish = Database::load_or_init(&db_file("spanish")).unwrap();
+        assert_eq!(spanish.all_words().unwrap().len(), 1);
+        drop(spanish);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unknown_language_has_no_db() {
+        let dir = std::env::temp_dir();
+        let db_path = dir.to_str().unwrap();
+        let result = Database::get_instance(db_path, &Language::new("klingon"));
+        assert!(matches!(result, Err(AppError::UnsupportedLanguage(_))));
+        assert!(!dir.join("klingon.db").exists());
+    }
+
+    #[test]
+    fn test_words_match_in_any_form() {
+        let mut db = Database::in_memory().unwrap();
+        db.set_morphology(Morphology::new(&Language::new("spanish").info().unwrap()))
+            .unwrap();
+        db.import_lemmas([("fui".to_string(), "ser".to_string())])
+            .unwrap();
+
+        db.import_lemmas([("hablaba".to_string(), "hablar".to_string())])
+            .unwrap();
+
+        db.add_word("hablar", "to speak").unwrap();
+        db.add_word("Fui", "").unwrap();
+        db.update_word_translation("ser", "to be").unwrap();
+
+        assert_eq!(db.get_translation("hablaba").unwrap().unwrap(), "to speak");
+        assert_eq!(db.get_translation("fui").unwrap().unwrap(), "to be");
+        assert_eq!(db.get_translation("casa").unwrap(), None);
+        let words = db.all_words().unwrap();
+        let ser = words.iter().find(|w| w.word == "ser").unwrap();
+        assert_eq!(ser.forms, vec!["fui".to_string()]);
+    }
+
+    #[test]
+    fn test_words_with_the_same_stem_are_different() {
+        let mut db = Database::in_memory().unwrap();
+        db.set_morphology(Morphology::new(&Language::new("spanish").info().unwrap()))
+            .unwrap();
+        db.add_word("casa", "house").unwrap();
+
+        assert_eq!(db.find_word("caso").unwrap(), None);
+        assert_eq!(db.get_translation("caso").unwrap(), None);
+        assert_eq!(
+            db.same_stem_words("caso").unwrap(),
+            vec!["casa".to_string()]
+        );
+
+        let added = db
+            .add_new_words(&[("caso".to_string(), "case".to_string())])
+            .unwrap();
+        assert_eq!(added, vec!["caso".to_string()]);
+        assert_eq!(db.get_translation("caso").unwrap().unwrap(), "case");
+        assert_eq!(db.get_translation("casa").unwrap().unwrap(), "house");
+        assert_eq!(
+            db.same_stem_words("casa").unwrap(),
+            vec!["caso".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_familiarity() {
+        let db = Database::in_memory().unwrap();
+        db.add_word("casa", "house").unwrap();
+        let familiarity = |db: &Database| db.all_words().unwrap()[0].familiarity;
+        assert_eq!(familiarity(&db), Familiarity::New);
+
+        for _ in 0..KNOWN_STREAK - 1 {
+            db.record_review("Casa", true).unwrap();
+        }
+        assert_eq!(familiarity(&db), Familiarity::Learning);
+        db.record_review("casa", true).unwrap();
+        assert_eq!(familiarity(&db), Familiarity::Known);
+
+        db.record_lookup("casa").unwrap();
+        assert_eq!(familiarity(&db), Familiarity::Learning);
+        db.record_review("perro", false).unwrap();
+
+        // Three forms of the same word are a single review.
+        let db = Database::in_memory().unwrap();
+        db.import_lemmas(
+            ["hablo", "hablas", "hablamos"].map(|form| (form.to_string(), "hablar".to_string())),
+        )
+        .unwrap();
+        db.add_word("hablar", "to speak").unwrap();
+        db.record_sentence_review("Hablo, hablas y hablamos.", true)
+            .unwrap();
+        let hablar = db
+            .all_words()
+            .unwrap()
+            .into_iter()
+            .find(|w| w.word == "hablar")
+            .unwrap();
+        assert_eq!(hablar.familiarity, Familiarity::Learning);
+    }
+
+    #[test]
+    fn test_coverage() {
+        let db = Database::in_memory().unwrap();
+        db.add_word("gato", "cat").unwrap();
+        db.add_word("come", "eats").unwrap();
+        let spanish = Language::new("spanish").info().unwrap();
+        let text = "El gato come 2 peces. ¡El gato!";
+
+        // Nothing was reviewed yet, so all my words are familiar.
+        let coverage = db.coverage(text, &spanish).unwrap();
+        assert_eq!(coverage.unknown_words, vec!["peces".to_string()]);
+        assert!((coverage.known_ratio - 6.0 / 7.0).abs() < 1e-9);
+
+        // Forms are matched like in lookups: by the lemma or a form seen before.
+        let mut forms = Database::in_memory().unwrap();
+        forms.set_morphology(Morphology::new(&spanish)).unwrap();
+        forms
+            .import_lemmas([("gatos".to_string(), "gato".to_string())])
+            .unwrap();
+        forms.add_word("gato", "cat").unwrap();
+        forms.add_word("Comen", "eat").unwrap();
+        forms.add_word("casa", "house").unwrap();
+        let coverage = forms
+            .coverage("Los gatos comen en caso de", &spanish)
+            .unwrap();
+        assert_eq!(coverage.unknown_words, vec!["caso".to_string()]);
+
+        // Then the new words are not known anymore, like in the prompts.
+        db.record_review("gato", true).unwrap();
+        let coverage = db.coverage(text, &spanish).unwrap();
+        assert_eq!(
+            coverage.unknown_words,
+            vec!["come".to_string(), "peces".to_string()]
+        );
+        assert!((coverage.known_ratio - 5.0 / 7.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_library() {
+        let db = Database::in_memory().unwrap();
+        let first = db.save_story(None, "Había una vez.", 1.0).unwrap();
+        let second = db
+            .save_story(Some("Gatos"), "El gato come peces.", 0.75)
+            .unwrap();
+        db.record_story_lookup(second, "Peces.").unwrap();
+        db.record_story_lookup(second, "peces").unwrap();
+
+        let stories = db.all_stories().unwrap();
+        assert_eq!(
+            stories.iter().map(|story| story.id).collect::<Vec<_>>(),
+            vec![second, first]
+        );
+        assert_eq!(stories[0].looked_up, vec!["peces".to_string()]);
+        assert_eq!(stories[0].title.as_deref(), Some("Gatos"));
+        assert!(stories[1].looked_up.is_empty());
+        assert_eq!(db.get_story(first).unwrap().unwrap().text, "Había una vez.");
+        assert!(db.get_story(first + second).unwrap().is_none());
+
+        let chapters = ["Uno.", "Dos.", "Tres."]
+            .map(|text| (text.to_string(), 1.0))
+            .to_vec();
+        let ids = db.save_book("El libro", &chapters).unwrap();
+        let stories = db.all_stories().unwrap();
+        assert_eq!(
+            stories.iter().map(|story| story.id).collect::<Vec<_>>()[..3],
+            ids
+        );
+        assert_eq!(stories[1].title.as_deref(), Some("El libro, 2/3"));
+        let chapter = db.get_story(ids[1]).unwrap().unwrap();
+        assert_eq!(chapter.text, "Dos.");
+        assert_eq!(chapter.title.as_deref(), Some("El libro, 2/3"));
+    }
+
+    #[test]
+    fn test_listening_schedule() {
+        let db = Database::in_memory().unwrap();
+        assert!(db.listened_recently().unwrap().is_empty());
+
+        let review = db.record_listening(1, 0.75, Grade::Hard).unwrap();
+        assert_eq!(review.grade, Grade::Hard);
+        db.record_listening(1, 1.0, Grade::Easy).unwrap();
+        db.record_listening(2, 0.0, Grade::Again).unwrap();
+        assert_eq!(db.listened_recently().unwrap(), HashSet::from([1, 2]));
+
+        let history = db.listening_history().unwrap();
+        assert_eq!(
+            history
+                .iter()
+                .map(|review| (review.flashcard_id, review.score, review.grade))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, 0.75, Grade::Hard),
+                (1, 1.0, Grade::Easy),
+                (2, 0.0, Grade::Again)
+            ]
+        );
+        let review_after_secs: i64 = db
+            .conn
+            .query_row(
+                "SELECT review_after_secs FROM listening_schedule WHERE flashcard_id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(review_after_secs, 43200 * 3);
+    }
 }
@@ -0,0 +1,310 @@
+//! Text of EPUB books, to read them in the learning language.
+
+#![cfg(feature = "ssr")]
+
+use std::io::{Cursor, Read};
+
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use zip::ZipArchive;
+
+/// A book split into chapters, in the reading order.
+#[derive(Debug, PartialEq)]
+pub struct Book {
+    pub title: Option<String>,
+    /// Text of each chapter, chapters without text are skipped.
+    pub chapters: Vec<String>,
+}
+
+/// Reads the chapters listed in the spine of the package document.
+pub fn parse_epub(bytes: &[u8]) -> Result<Book, String> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(|e| e.to_string())?;
+
+    let container = read_file(&mut archive, "META-INF/container.xml")?;
+    let opf_path = find_attribute(&container, "rootfile", "full-path")?
+        .ok_or("container.xml has no rootfile")?;
+    let opf = read_file(&mut archive, &opf_path)?;
+    let base_dir = match opf_path.rsplit_once('/') {
+        Some((dir, _)) => format!("{}/", dir),
+        None => String::new(),
+    };
+
+    let package = parse_package(&opf)?;
+    let mut chapters = Vec::new();
+    for href in package.spine {
+        let path = resolve_href(&base_dir, &href);
+        // The rest of the book is still worth reading.
+        match read_file(&mut archive, &path).and_then(|html| html_to_text(&html)) {
+            Ok(text) if !text.is_empty() => chapters.push(text),
+            Ok(_) => {}
+            Err(e) => log::warn!("Skipped the chapter {}: {}", path, e),
+        }
+    }
+    Ok(Book {
+        title: package.title,
+        chapters,
+    })
+}
+
+/// Path in the archive of a file referenced from the package document: hrefs are URLs,
+/// relative to the document and percent-encoded, e.g. "../Text/cap%C3%ADtulo%201.xhtml#p1".
+fn resolve_href(base_dir: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let mut segments = Vec::new();
+    for segment in format!("{}{}", base_dir, percent_decode(href)).split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment.to_string()),
+        }
+    }
+    segments.join("/")
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn read_file(archive: &mut ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<String, String> {
+    let mut file = archive
+        .by_name(name)
+        .map_err(|e| format!("{}: {}", name, e))?;
+    let mut content = String::new();
+    file.read_to_string(&mut content)
+        .map_err(|e| format!("{}: {}", name, e))?;
+    Ok(content)
+}
+
+fn reader(xml: &str) -> Reader<&[u8]> {
+    let mut reader = Reader::from_str(xml);
+    // XHTML in the wild isn't always well-formed.
+    reader.config_mut().check_end_names = false;
+    reader
+}
+
+/// Value of the attribute of the first element with the given name.
+fn find_attribute(xml: &str, element: &str, attribute: &str) -> Result<Option<String>, String> {
+    let mut reader = reader(xml);
+    loop {
+        match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == element.as_bytes() => {
+                return attribute_value(&e, attribute);
+            }
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+fn attribute_value(
+    element: &quick_xml::events::BytesStart,
+    attribute: &str,
+) -> Result<Option<String>, String> {
+    let value = element
+        .try_get_attribute(attribute)
+        .map_err(|e| e.to_string())?
+        .map(|attr| attr.unescape_value().map(|value| value.into_owned()))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    Ok(value)
+}
+
+struct Package {
+    title: Option<String>,
+    /// Files of the chapters in the reading order.
+    spine: Vec<String>,
+}
+
+fn parse_package(opf: &str) -> Result<Package, String> {
+    let mut reader = reader(opf);
+    let mut title = None;
+    let mut in_title = false;
+    let mut manifest = Vec::new();
+    let mut spine = Vec::new();
+    loop {
+        match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                b"title" => in_title = title.is_none(),
+                b"item" => {
+                    if let (Some(id), Some(href)) =
+                        (attribute_value(&e, "id")?, attribute_value(&e, "href")?)
+                    {
+                        manifest.push((id, href));
+                    }
+                }
+                b"itemref" => {
+                    if let Some(idref) = attribute_value(&e, "idref")? {
+                        spine.push(idref);
+                    }
+                }
+                _ => {}
+            },
+            Event::Text(e) if in_title => {
+                let text = e.unescape().map_err(|e| e.to_string())?;
+                title = Some(text.trim().to_string()).filter(|t| !t.is_empty());
+                in_title = false;
+            }
+            Event::End(_) => in_title = false,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    let spine = spine
+        .into_iter()
+        .filter_map(|idref| {
+            manifest
+                .iter()
+                .find(|(id, _)| *id == idref)
+                .map(|(_, href)| href.clone())
+        })
+        .collect();
+    Ok(Package { title, spine })
+}
+
+/// Text of the body, a line per paragraph, heading or list item.
+fn html_to_text(html: &str) -> Result<String, String> {
+    let mut reader = reader(html);
+    let mut text = String::new();
+    // Text inside <head>, <script> and <style> isn't shown.
+    let mut hidden = 0;
+    loop {
+        match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"head" | b"script" | b"style" => hidden += 1,
+                name if is_block(name) => text.push('\n'),
+                _ => {}
+            },
+            Event::End(e) => match e.local_name().as_ref() {
+                b"head" | b"script" | b"style" => hidden -= 1,
+                name if is_block(name) => text.push('\n'),
+                _ => {}
+            },
+            Event::Empty(e) if e.local_name().as_ref() == b"br" => text.push('\n'),
+            Event::Text(e) if hidden == 0 => {
+                // &nbsp; is the most common HTML entity, unknown to XML.
+                let t = match e.unescape_with(|entity| {
+                    resolve_predefined_entity(entity).or((entity == "nbsp").then_some(" "))
+                }) {
+                    Ok(t) => t.into_owned(),
+                    Err(_) => String::from_utf8_lossy(&e).into_owned(),
+                };
+                // Line breaks come from the markup only.
+                text.push_str(&t.replace(['\n', '\r'], " "));
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(text
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn is_block(name: &[u8]) -> bool {
+    matches!(
+        name,
+        b"p" | b"div"
+            | b"h1"
+            | b"h2"
+            | b"h3"
+            | b"h4"
+            | b"h5"
+            | b"h6"
+            | b"li"
+            | b"tr"
+            | b"blockquote"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    fn epub(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_parse_epub() {
+        let bytes = epub(&[
+            (
+                "META-INF/container.xml",
+                r#"<container><rootfiles><rootfile full-path="OEBPS/content.opf"/></rootfiles></container>"#,
+            ),
+            (
+                "OEBPS/content.opf",
+                r#"<package><metadata><dc:title>El libro</dc:title></metadata>
+                <manifest>
+                    <item id="c2" href="text/c2.xhtml"/>
+                    <item id="c1" href="text/c1.xhtml"/>
+                    <item id="c3" href="../OEBPS/text/cap%C3%ADtulo%203.xhtml#inicio"/>
+                    <item id="missing" href="text/missing.xhtml"/>
+                    <item id="cover" href="text/cover.xhtml"/>
+                </manifest>
+                <spine>
+                    <itemref idref="cover"/><itemref idref="c1"/><itemref idref="c2"/>
+                    <itemref idref="missing"/><itemref idref="c3"/>
+                </spine></package>"#,
+            ),
+            ("OEBPS/text/cover.xhtml", "<html><body><img src='cover.jpg'/></body></html>"),
+            (
+                "OEBPS/text/c1.xhtml",
+                "<html><head><title>Uno</title><style>p {}</style></head>
+                <body><h1>Capítulo 1</h1><p>Había una\n   vez&nbsp;un <i>gato</i>.</p><p>Fin &amp; ya<br/>adiós</p></body></html>",
+            ),
+            ("OEBPS/text/c2.xhtml", "<html><body><p>Dos</p></body></html>"),
+            (
+                "OEBPS/text/capítulo 3.xhtml",
+                "<html><body><p>Tres</p></body></html>",
+            ),
+        ]);
+
+        let book = parse_epub(&bytes).unwrap();
+        assert_eq!(book.title.as_deref(), Some("El libro"));
+        assert_eq!(
+            book.chapters,
+            vec![
+                "Capítulo 1\nHabía una vez un gato.\nFin & ya\nadiós".to_string(),
+                "Dos".to_string(),
+                "Tres".to_string()
+            ]
+        );
+        assert_eq!(
+            resolve_href("OEBPS/content/", "./../Text/a%20b.xhtml#p1"),
+            "OEBPS/Text/a b.xhtml"
+        );
+        assert!(parse_epub(b"not a zip").is_err());
+    }
+}
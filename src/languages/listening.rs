@@ -0,0 +1,190 @@
+//! Listening reviews: the answer of a sentence card is played instead of shown, then typed
+//! or matched with its translation. They are scheduled apart from the reading reviews.
+
+#![cfg(feature = "ssr")]
+
+use std::collections::HashSet;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::db::Database as FlashcardsDb;
+use crate::errors::AppError;
+use crate::languages::model::{is_conjugation_drill, ListeningCard, ListeningReview};
+use crate::languages::morphology::tokenize;
+use crate::model::{Flashcard, Grade};
+use crate::settings::Language;
+use crate::words_db;
+
+/// Translations to pick from, the right one included.
+pub const TRANSLATION_OPTIONS: usize = 4;
+
+/// From 0.0 to 1.0: the share of the words heard right and in the right order.
+/// Extra words lower the score just like missing ones.
+pub fn dictation_score(heard: &str, answer: &str) -> f64 {
+    let heard = tokenize(heard);
+    let answer = tokenize(answer);
+    let len = heard.len().max(answer.len());
+    if len == 0 {
+        return 1.0;
+    }
+    // Longest common subsequence of the words.
+    let mut lcs = vec![vec![0; answer.len() + 1]; heard.len() + 1];
+    for (i, heard_word) in heard.iter().enumerate() {
+        for (j, answer_word) in answer.iter().enumerate() {
+            lcs[i + 1][j + 1] = if heard_word == answer_word {
+                lcs[i][j] + 1
+            } else {
+                lcs[i][j + 1].max(lcs[i + 1][j])
+            };
+        }
+    }
+    lcs[heard.len()][answer.len()] as f64 / len as f64
+}
+
+pub fn grade_for_score(score: f64) -> Grade {
+    match score {
+        s if s >= 1.0 => Grade::Easy,
+        s if s >= 0.8 => Grade::Good,
+        s if s >= 0.5 => Grade::Hard,
+        _ => Grade::Again,
+    }
+}
+
+/// The question of the card and the questions of the cards closest in length, shuffled anew
+/// for each review: the right one can't be remembered by its position.
+pub fn translation_options(
+    card: &Flashcard,
+    cards: &[Flashcard],
+    rng: &mut impl Rng,
+) -> Vec<String> {
+    let mut others = cards
+        .iter()
+        .filter(|other| other.question != card.question)
+        .collect::<Vec<_>>();
+    others.sort_by_key(|other| (other.question.len().abs_diff(card.question.len()), other.id));
+    let mut seen = HashSet::new();
+    let mut options = others
+        .into_iter()
+        .map(|other| other.question.clone())
+        .filter(|question| seen.insert(question.clone()))
+        .take(TRANSLATION_OPTIONS - 1)
+        .collect::<Vec<_>>();
+    options.push(card.question.clone());
+    options.shuffle(rng);
+    options
+}
+
+/// The cards that were never listened to, or are due. Conjugation drills are left out:
+/// a form alone isn't a sentence to listen to, nor a translation to pick.
+pub fn listening_cards(
+    cards: Vec<Flashcard>,
+    not_due: &HashSet<i64>,
+    rng: &mut impl Rng,
+) -> Vec<ListeningCard> {
+    let cards = cards
+        .into_iter()
+        .filter(|card| !is_conjugation_drill(card))
+        .collect::<Vec<_>>();
+    cards
+        .iter()
+        .filter(|card| !not_due.contains(&card.id))
+        .map(|card| ListeningCard {
+            card: card.clone(),
+            translations: translation_options(card, &cards, rng),
+        })
+        .collect()
+}
+
+/// Sentence cards of the language that were never listened to, or are due.
+pub fn cards_to_listen(lang: &Language) -> Result<Vec<ListeningCard>, AppError> {
+    let cards = {
+        let db = FlashcardsDb::get_instance().unwrap().lock().unwrap();
+        db.all_cards(Some(lang.to_string()))?
+    };
+    let not_due = words_db!(lang).listened_recently()?;
+    Ok(listening_cards(cards, &not_due, &mut rand::rng()))
+}
+
+/// Reschedules the listening review of the card according to the score.
+pub fn record_listening(
+    lang: &Language,
+    card_id: i64,
+    score: f64,
+) -> Result<ListeningReview, AppError> {
+    Ok(words_db!(lang).record_listening(card_id, score, grade_for_score(score))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::languages::model::CONJUGATION_TAG;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_listening_scores() {
+        let answer = "El gato come peces.";
+        assert_eq!(dictation_score("el gato come peces", answer), 1.0);
+        assert_eq!(dictation_score("El perro come peces", answer), 0.75);
+        assert_eq!(dictation_score("El gato come muchos peces", answer), 0.8);
+        assert_eq!(dictation_score("", answer), 0.0);
+        assert_eq!(grade_for_score(1.0), Grade::Easy);
+        assert_eq!(grade_for_score(0.75), Grade::Hard);
+        assert_eq!(grade_for_score(0.0), Grade::Again);
+
+        let cards = [
+            "The cat",
+            "The dog eats",
+            "A house",
+            "The cat",
+            "I speak Spanish well",
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(id, question)| {
+            let mut card = Flashcard::new(question.to_string(), String::new());
+            card.id = id as i64 + 1;
+            card
+        })
+        .collect::<Vec<_>>();
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut options = translation_options(&cards[1], &cards, &mut rng);
+        options.sort();
+        assert_eq!(
+            options,
+            vec!["A house", "I speak Spanish well", "The cat", "The dog eats"]
+        );
+        // The right one moves from one review to another.
+        let positions = (0..20)
+            .map(|_| {
+                translation_options(&cards[1], &cards, &mut rng)
+                    .iter()
+                    .position(|option| option == "The dog eats")
+                    .unwrap()
+            })
+            .collect::<HashSet<_>>();
+        assert!(positions.len() > 1);
+        assert_eq!(
+            translation_options(&cards[0], &cards[..1], &mut rng),
+            vec!["The cat"]
+        );
+
+        let mut drill = Flashcard::new("hablar, presente, yo → ?".to_string(), "hablo".to_string());
+        drill.id = 6;
+        drill.tags = vec!["spanish".to_string(), CONJUGATION_TAG.to_string()];
+        let mut cards = cards;
+        cards.push(drill);
+        let listening = listening_cards(cards, &HashSet::from([2]), &mut rng);
+        assert_eq!(
+            listening
+                .iter()
+                .map(|listening| listening.card.id)
+                .collect::<Vec<_>>(),
+            vec![1, 3, 4, 5]
+        );
+        assert!(listening
+            .iter()
+            .all(|listening| !listening.translations.iter().any(|t| t.contains('→'))));
+    }
+}
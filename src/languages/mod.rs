@@ -3,5 +3,12 @@
 
 pub mod ai;
 pub mod components;
-mod db;
-mod model;
+pub mod conjugation;
+pub mod db;
+pub mod epub;
+pub mod listening;
+pub mod model;
+pub mod morphology;
+pub mod progress;
+pub mod sentence_card;
+pub mod translation;
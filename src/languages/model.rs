@@ -1,11 +1,115 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::model::{Flashcard, Grade};
+
+/// How well I know a word.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Familiarity {
+    /// Never reviewed.
+    #[default]
+    New,
+    /// Reviewed, but forgotten or looked up recently.
+    Learning,
+    /// Remembered in several reviews in a row.
+    Known,
+}
+
+impl Familiarity {
+    pub const ALL: [Familiarity; 3] = [Familiarity::New, Familiarity::Learning, Familiarity::Known];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Familiarity::New => "new",
+            Familiarity::Learning => "learning",
+            Familiarity::Known => "known",
+        }
+    }
+}
+
+impl FromStr for Familiarity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Familiarity::ALL
+            .into_iter()
+            .find(|familiarity| familiarity.as_str() == s)
+            .ok_or_else(|| format!("Unknown familiarity: {}", s))
+    }
+}
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct Word {
     pub word: String,
     pub translation: Option<String>,
     pub created_at: DateTime<Utc>,
+    /// Inflected forms of the word that were seen, e.g. "hablaba" for "hablar".
+    #[serde(default)]
+    pub forms: Vec<String>,
+    #[serde(default)]
+    pub familiarity: Familiarity,
+}
+
+/// Words I can be expected to understand, or all of them while nothing was reviewed yet.
+pub fn familiar_words(words: &[Word]) -> Vec<&Word> {
+    let familiar = words
+        .iter()
+        .filter(|word| word.familiarity != Familiarity::New)
+        .collect::<Vec<_>>();
+    if familiar.is_empty() {
+        words.iter().collect()
+    } else {
+        familiar
+    }
+}
+
+/// How much of a text I understand.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Coverage {
+    /// From 0.0 to 1.0: the share of the words in the text that I'm familiar with, see
+    /// `familiar_words`. Articles, stop-words and numbers count as known.
+    pub known_ratio: f64,
+    /// Normalized words that are not in my vocabulary, without duplicates.
+    pub unknown_words: Vec<String>,
+}
+
+/// A story written with the words I know.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GeneratedStory {
+    pub text: String,
+    pub coverage: Coverage,
+}
+
+/// A generated story, or an imported text, kept in the library to re-read it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedStory {
+    pub id: i64,
+    /// Only imported texts have a title.
+    pub title: Option<String>,
+    pub text: String,
+    /// Coverage when the story was written, see `Coverage::known_ratio`.
+    pub known_ratio: f64,
+    pub created_at: DateTime<Utc>,
+    /// Words looked up while reading the story.
+    pub looked_up: Vec<String>,
+}
+
+/// All the conjugation drills have this tag, besides the language and the tense.
+pub const CONJUGATION_TAG: &str = "conjugation";
+
+/// Drills are tagged with the language like sentence cards, but their answers are single forms.
+pub fn is_conjugation_drill(card: &Flashcard) -> bool {
+    card.tags.iter().any(|tag| tag == CONJUGATION_TAG)
+}
+
+/// Forms of a verb in a tense, as (person, form) pairs in the order of
+/// `LanguageInfo::persons`, e.g. ("nosotros", "hablamos").
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Conjugation {
+    pub verb: String,
+    pub tense: String,
+    pub forms: Vec<(String, String)>,
 }
 
 /// A sentence with a new word and its translation for iterative language learning.
@@ -15,3 +119,30 @@ pub struct NewSentence {
     pub new_word: String,
     pub translation: String,
 }
+
+/// A generated sentence kept in the library.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSentence {
+    pub id: i64,
+    pub sentence: NewSentence,
+    pub known_ratio: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A sentence card to review by ear: its answer is played, not shown.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListeningCard {
+    pub card: Flashcard,
+    /// Questions to pick the one of the card from.
+    pub translations: Vec<String>,
+}
+
+/// A listening review of a sentence card.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListeningReview {
+    pub flashcard_id: i64,
+    pub review_date: DateTime<Utc>,
+    /// From 0.0 to 1.0: the share of the words heard right, or 1.0 for the right translation.
+    pub score: f64,
+    pub grade: Grade,
+}
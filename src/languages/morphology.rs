@@ -0,0 +1,124 @@
+//! Matches inflected words with their dictionary form (lemma), so that "hablaba" finds "hablar".
+//! Lemmas come from an imported lemma list. The Snowball stemmer only suggests related words:
+//! different words can share a stem, e.g. "casa" and "caso".
+
+#![cfg(feature = "ssr")]
+
+use rust_stemmers::{Algorithm, Stemmer};
+
+use crate::languages::translation::parse_dictionary;
+use crate::settings::LanguageInfo;
+
+/// Morphology rules of a language. Without a stemmer there are no suggestions by the stem.
+#[derive(Default)]
+pub struct Morphology {
+    stemmer: Option<Stemmer>,
+}
+
+impl Morphology {
+    pub fn new(lang: &LanguageInfo) -> Self {
+        Self {
+            stemmer: lang
+                .stemmer
+                .as_deref()
+                .and_then(algorithm)
+                .map(Stemmer::create),
+        }
+    }
+
+    /// Lowercase word without the surrounding punctuation.
+    pub fn normalize(word: &str) -> String {
+        word.trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase()
+    }
+
+    /// Different forms of a word usually share the stem, e.g. "habl" for "hablar" and "hablaba".
+    pub fn stem(&self, word: &str) -> String {
+        let word = Self::normalize(word);
+        match &self.stemmer {
+            Some(stemmer) => stemmer.stem(&word).into_owned(),
+            None => word,
+        }
+    }
+}
+
+/// Normalized words of the text, e.g. ["l", "homme", "est", "là"] for "L'homme est là!".
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '-')
+        .map(Morphology::normalize)
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Snowball algorithm by the language name used in `LanguageInfo::stemmer`.
+fn algorithm(name: &str) -> Option<Algorithm> {
+    let algorithm = match name {
+        "arabic" => Algorithm::Arabic,
+        "danish" => Algorithm::Danish,
+        "dutch" => Algorithm::Dutch,
+        "english" => Algorithm::English,
+        "finnish" => Algorithm::Finnish,
+        "french" => Algorithm::French,
+        "german" => Algorithm::German,
+        "greek" => Algorithm::Greek,
+        "hungarian" => Algorithm::Hungarian,
+        "italian" => Algorithm::Italian,
+        "norwegian" => Algorithm::Norwegian,
+        "portuguese" => Algorithm::Portuguese,
+        "romanian" => Algorithm::Romanian,
+        "russian" => Algorithm::Russian,
+        "spanish" => Algorithm::Spanish,
+        "swedish" => Algorithm::Swedish,
+        "tamil" => Algorithm::Tamil,
+        "turkish" => Algorithm::Turkish,
+        _ => {
+            log::warn!("Unknown stemmer: {}", name);
+            return None;
+        }
+    };
+    Some(algorithm)
+}
+
+/// Parses a lemma list with a `lemma<TAB>form` per line, like the ones from
+/// the lemmatization-lists project. Returns (form, lemma) pairs.
+pub fn parse_lemmas(text: &str) -> Vec<(String, String)> {
+    parse_dictionary(text)
+        .into_iter()
+        .map(|(lemma, form)| (form.to_lowercase(), lemma))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Language;
+
+    #[test]
+    fn test_stem() {
+        let spanish = Morphology::new(&Language::new("spanish").info().unwrap());
+        assert_eq!(spanish.stem("Hablaba,"), spanish.stem("hablar"));
+        assert_ne!(spanish.stem("casa"), spanish.stem("hablar"));
+
+        let no_stemmer = Morphology::default();
+        assert_eq!(no_stemmer.stem("¡Hablaba!"), "hablaba");
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("¿Dónde está el baño? L'homme -- bien-être."),
+            vec!["dónde", "está", "el", "baño", "l", "homme", "bien-être"]
+        );
+    }
+
+    #[test]
+    fn test_parse_lemmas() {
+        assert_eq!(
+            parse_lemmas("ser\tEra\nser\tfui\n"),
+            vec![
+                ("era".to_string(), "ser".to_string()),
+                ("fui".to_string(), "ser".to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,35 @@
+//! Tracks how well I know the words of my vocabulary.
+
+#![cfg(feature = "ssr")]
+
+use crate::errors::AppError;
+use crate::model::Flashcard;
+use crate::settings::{all_languages, Language};
+use crate::words_db;
+
+/// Updates the familiarity of the words in the answer of a sentence card.
+/// Sentence cards are tagged with the language name, e.g. "spanish"; other cards are ignored.
+/// The review of the card is already saved by then, so failures are only logged.
+pub fn record_card_review(card: &Flashcard, remembered: bool) {
+    let languages = all_languages()
+        .into_iter()
+        .filter(|info| card.tags.iter().any(|tag| tag == info.name.as_str()));
+    for info in languages {
+        if let Err(e) = record_sentence_review(&info.name, &card.answer, remembered) {
+            log::warn!(
+                "Failed to update the {} words of card #{}: {}",
+                info.name,
+                card.id,
+                e
+            );
+        }
+    }
+}
+
+fn record_sentence_review(
+    lang: &Language,
+    sentence: &str,
+    remembered: bool,
+) -> Result<(), AppError> {
+    Ok(words_db!(lang).record_sentence_review(sentence, remembered)?)
+}
@@ -0,0 +1,111 @@
+//! Flashcards made from sentences in the learning language, with a gloss of their words.
+
+#![cfg(feature = "ssr")]
+
+use std::collections::HashSet;
+
+use crate::db::Database as FlashcardsDb;
+use crate::errors::AppError;
+use crate::languages::morphology::tokenize;
+use crate::languages::translation::{Translator, TranslatorChain};
+use crate::model::Flashcard;
+use crate::settings::{Language, LanguageInfo};
+use crate::words_db;
+
+/// Translation of each word of the sentence, `None` when no translator knows it.
+/// Articles and stop-words are skipped, the words are in the order of the sentence.
+pub async fn gloss(
+    sentence: &str,
+    lang: &LanguageInfo,
+    translator: &impl Translator,
+) -> Result<Vec<(String, Option<String>)>, AppError> {
+    let mut words = tokenize(sentence);
+    let mut seen = HashSet::new();
+    words.retain(|word| !lang.is_skipped_word(word) && seen.insert(word.clone()));
+
+    let mut gloss = Vec::new();
+    for word in words {
+        let translation = translator.translate(&word).await?;
+        gloss.push((word, translation));
+    }
+    Ok(gloss)
+}
+
+/// Markdown list for `Flashcard::examples`.
+pub fn format_gloss(gloss: &[(String, Option<String>)]) -> String {
+    gloss
+        .iter()
+        .map(|(word, translation)| {
+            format!("- **{}**: {}", word, translation.as_deref().unwrap_or("?"))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Adds a card with the translation as the question and the sentence as the answer,
+/// tagged with the language. The sentence is translated when `translation` is empty.
+/// Once the card is added, the words of the sentence that are not in my vocabulary are added
+/// to it, unless there is no translation for them. Returns the card id.
+pub async fn create_sentence_card(
+    lang: &Language,
+    sentence: &str,
+    translation: Option<String>,
+) -> Result<i64, AppError> {
+    let info = lang.info()?;
+    let translator = TranslatorChain::from_settings(&info)?;
+    let translation = match translation.filter(|t| !t.trim().is_empty()) {
+        Some(translation) => Some(translation),
+        None => translator.translate(sentence).await?,
+    };
+    let Some(translation) = translation else {
+        return Err(AppError::NoTranslation(sentence.trim().to_string()));
+    };
+    let gloss = gloss(sentence, &info, &translator).await?;
+
+    let mut card = Flashcard::new(translation.trim().to_string(), sentence.trim().to_string());
+    card.examples = Some(format_gloss(&gloss));
+    card.tags = vec![lang.to_string()];
+    card.source = Some("learning-languages app".to_string());
+
+    let card_id = FlashcardsDb::get_instance()
+        .map_err(|e| AppError::DuckdbError(e.to_string()))?
+        .lock()
+        .unwrap()
+        .add_card(&card)
+        .map_err(|e| AppError::DuckdbError(e.to_string()))?;
+
+    let new_words = gloss
+        .into_iter()
+        .filter_map(|(word, translation)| Some((word, translation?)))
+        .collect::<Vec<_>>();
+    words_db!(lang).add_new_words(&new_words)?;
+    Ok(card_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::languages::db::Database;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_gloss() {
+        let db: &'static Mutex<Database> =
+            Box::leak(Box::new(Mutex::new(Database::in_memory().unwrap())));
+        db.lock().unwrap().add_word("gato", "cat").unwrap();
+        let translator = TranslatorChain::new(db, "en".to_string(), None);
+        let spanish = Language::new("spanish").info().unwrap();
+
+        let gloss = gloss("El gato, el perro y el gato.", &spanish, &translator)
+            .await
+            .unwrap();
+        assert_eq!(
+            gloss,
+            vec![
+                ("gato".to_string(), Some("cat".to_string())),
+                ("perro".to_string(), None)
+            ]
+        );
+        assert_eq!(format_gloss(&gloss), "- **gato**: cat\n- **perro**: ?");
+    }
+}
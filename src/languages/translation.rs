@@ -0,0 +1,291 @@
+//! Translations from the learning language, tried from the cheapest source to the most expensive:
+//! the vocabulary, the imported dictionary, the cache and finally an online translator.
+
+#![cfg(feature = "ssr")]
+
+use std::future::Future;
+use std::sync::Mutex;
+
+use translators::Translator as _;
+
+use crate::errors::AppError;
+use crate::languages::db::Database;
+use crate::llm::{prompt_parsed, require_xml_tag, LlmResponse, Provider};
+use crate::settings::{LanguageInfo, OnlineTranslatorKind, Settings};
+
+pub trait Translator {
+    /// Translates the text, `None` when this translator doesn't know it.
+    fn translate(
+        &self,
+        text: &str,
+    ) -> impl Future<Output = Result<Option<String>, AppError>> + Send;
+}
+
+static TRANSLATE_PROMPT: &str = "
+Translate this {lang} text into the language with ISO code '{target}':
+
+<text>
+{text}
+</text>
+
+Output only the translation in this format:
+
+<translation>
+...
+</translation>
+";
+
+/// Translations of my words.
+pub struct VocabularyTranslator {
+    db: &'static Mutex<Database>,
+}
+
+impl Translator for VocabularyTranslator {
+    async fn translate(&self, text: &str) -> Result<Option<String>, AppError> {
+        let translation = self.db.lock().unwrap().get_translation(text)?;
+        // Words are added without a translation when populated from flashcards.
+        Ok(translation.filter(|t| !t.is_empty()))
+    }
+}
+
+/// An imported bilingual dictionary, see `parse_dictionary()`.
+pub struct DictionaryTranslator {
+    db: &'static Mutex<Database>,
+}
+
+impl Translator for DictionaryTranslator {
+    async fn translate(&self, text: &str) -> Result<Option<String>, AppError> {
+        Ok(self.db.lock().unwrap().dictionary_lookup(text)?)
+    }
+}
+
+pub struct GoogleTranslator {
+    source: String,
+    target: String,
+}
+
+impl Translator for GoogleTranslator {
+    async fn translate(&self, text: &str) -> Result<Option<String>, AppError> {
+        let translation = translators::GoogleTranslator::default()
+            .translate_async(text, &self.source, &self.target)
+            .await?;
+        Ok(Some(translation))
+    }
+}
+
+pub struct LlmTranslator {
+    llm: Provider,
+    lang: String,
+    target: String,
+}
+
+struct Translation(String);
+
+impl LlmResponse for Translation {
+    const FORMAT: &'static str = "<translation>\n...\n</translation>";
+
+    fn parse(response: &str) -> Result<Self, AppError> {
+        Ok(Self(require_xml_tag(response, "translation")?))
+    }
+}
+
+impl Translator for LlmTranslator {
+    async fn translate(&self, text: &str) -> Result<Option<String>, AppError> {
+        let prompt = TRANSLATE_PROMPT
+            .replace("{lang}", &self.lang)
+            .replace("{target}", &self.target)
+            .replace("{text}", text);
+        let Translation(translation) = prompt_parsed(&self.llm, &prompt).await?;
+        Ok(Some(translation))
+    }
+}
+
+pub enum OnlineTranslator {
+    Google(GoogleTranslator),
+    Llm(LlmTranslator),
+}
+
+impl OnlineTranslator {
+    fn name(&self) -> &'static str {
+        match self {
+            OnlineTranslator::Google(_) => "google",
+            OnlineTranslator::Llm(_) => "llm",
+        }
+    }
+}
+
+impl Translator for OnlineTranslator {
+    async fn translate(&self, text: &str) -> Result<Option<String>, AppError> {
+        match self {
+            OnlineTranslator::Google(translator) => translator.translate(text).await,
+            OnlineTranslator::Llm(translator) => translator.translate(text).await,
+        }
+    }
+}
+
+/// Tries the local translators first, then the online one. Online translations are cached.
+pub struct TranslatorChain {
+    db: &'static Mutex<Database>,
+    /// ISO code of the language to translate into, the cache is kept per target language.
+    target: String,
+    vocabulary: VocabularyTranslator,
+    dictionary: DictionaryTranslator,
+    /// `None` in offline mode.
+    online: Option<OnlineTranslator>,
+}
+
+impl TranslatorChain {
+    pub fn new(
+        db: &'static Mutex<Database>,
+        target: String,
+        online: Option<OnlineTranslator>,
+    ) -> Self {
+        Self {
+            db,
+            target,
+            vocabulary: VocabularyTranslator { db },
+            dictionary: DictionaryTranslator { db },
+            online,
+        }
+    }
+
+    pub fn from_settings(lang: &LanguageInfo) -> Result<Self, AppError> {
+        let settings = Settings::get();
+        let db = Database::get_instance(&settings.db_path, &lang.name)?;
+        let target = settings.translation.target_language.clone();
+        if settings.translation.offline {
+            return Ok(Self::new(db, target, None));
+        }
+
+        let online = match settings.translation.online {
+            OnlineTranslatorKind::Google => OnlineTranslator::Google(GoogleTranslator {
+                source: lang.translator_code().to_string(),
+                target: target.clone(),
+            }),
+            OnlineTranslatorKind::Llm => OnlineTranslator::Llm(LlmTranslator {
+                llm: Provider::from_settings(),
+                lang: lang.name.to_string(),
+                target: target.clone(),
+            }),
+        };
+        Ok(Self::new(db, target, Some(online)))
+    }
+}
+
+impl Translator for TranslatorChain {
+    async fn translate(&self, text: &str) -> Result<Option<String>, AppError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(None);
+        }
+        if let Some(translation) = self.vocabulary.translate(text).await? {
+            return Ok(Some(translation));
+        }
+        if let Some(translation) = self.dictionary.translate(text).await? {
+            return Ok(Some(translation));
+        }
+        let cached = self
+            .db
+            .lock()
+            .unwrap()
+            .cached_translation(text, &self.target)?;
+        if cached.is_some() {
+            return Ok(cached);
+        }
+
+        let Some(online) = &self.online else {
+            return Ok(None);
+        };
+        let translation = online.translate(text).await?;
+        if let Some(translation) = &translation {
+            self.db.lock().unwrap().cache_translation(
+                text,
+                &self.target,
+                translation,
+                online.name(),
+            )?;
+        }
+        Ok(translation)
+    }
+}
+
+/// Parses a dictionary with a `word<TAB>translation` per line, e.g. converted from
+/// a FreeDict or Wiktionary dump. Empty lines and lines starting with `#` are skipped.
+pub fn parse_dictionary(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(word, translation)| (word.trim().to_lowercase(), translation.trim().to_string()))
+        .filter(|(word, translation)| !word.is_empty() && !translation.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::MockProvider;
+
+    fn test_db() -> &'static Mutex<Database> {
+        Box::leak(Box::new(Mutex::new(Database::in_memory().unwrap())))
+    }
+
+    #[test]
+    fn test_parse_dictionary() {
+        let entries = parse_dictionary("# FreeDict spa-eng\nHablar\tto speak\n\nbroken line\n");
+        assert_eq!(
+            entries,
+            vec![("hablar".to_string(), "to speak".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_translator_chain() {
+        let db = test_db();
+        {
+            let db = db.lock().unwrap();
+            db.add_word("casa", "house").unwrap();
+            db.add_word("perro", "").unwrap();
+            db.import_dictionary(parse_dictionary("perro\tdog\nperro\thound\nser\tto be"))
+                .unwrap();
+            db.import_lemmas([("fui".to_string(), "ser".to_string())])
+                .unwrap();
+        }
+        let llm = LlmTranslator {
+            llm: Provider::Mock(MockProvider::new(["<translation>cat</translation>"])),
+            lang: "spanish".to_string(),
+            target: "en".to_string(),
+        };
+        let chain = TranslatorChain::new(db, "en".to_string(), Some(OnlineTranslator::Llm(llm)));
+
+        assert_eq!(chain.translate("casa").await.unwrap().unwrap(), "house");
+        assert_eq!(
+            chain.translate("perro").await.unwrap().unwrap(),
+            "dog; hound"
+        );
+        assert_eq!(
+            chain.translate("Perro,").await.unwrap().unwrap(),
+            "dog; hound"
+        );
+        assert_eq!(chain.translate("fui").await.unwrap().unwrap(), "to be");
+        // The second time it comes from the cache, the mock has no more responses.
+        assert_eq!(chain.translate("gato").await.unwrap().unwrap(), "cat");
+        assert_eq!(chain.translate("gato").await.unwrap().unwrap(), "cat");
+
+        let offline = TranslatorChain::new(db, "en".to_string(), None);
+        assert_eq!(offline.translate("gato").await.unwrap().unwrap(), "cat");
+        assert_eq!(offline.translate("raton").await.unwrap(), None);
+        let french = TranslatorChain::new(db, "fr".to_string(), None);
+        assert_eq!(french.translate("gato").await.unwrap(), None);
+
+        // Importing the dictionary again doesn't repeat the translations.
+        db.lock()
+            .unwrap()
+            .import_dictionary(parse_dictionary("perro\tdog\nperro\thound"))
+            .unwrap();
+        assert_eq!(
+            offline.translate("perro").await.unwrap().unwrap(),
+            "dog; hound"
+        );
+    }
+}
@@ -1,12 +1,18 @@
 #![recursion_limit = "256"]
 
 pub mod app;
+pub mod assistant;
+pub mod choices;
 mod components;
 pub mod db;
-mod errors;
+mod diagram;
+pub mod duplicates;
+pub mod errors;
 pub mod languages;
-mod model;
+pub mod llm;
+pub mod model;
 pub mod settings;
+pub mod tui;
 
 #[cfg(feature = "hydrate")]
 #[wasm_bindgen::prelude::wasm_bindgen]
@@ -0,0 +1,370 @@
+//! LLM providers the app can talk to.
+
+#![cfg(feature = "ssr")]
+
+#[cfg(test)]
+use std::collections::VecDeque;
+use std::future::Future;
+#[cfg(test)]
+use std::sync::Mutex;
+
+use rig::client::CompletionClient;
+use rig::completion::Prompt;
+use rig::providers::anthropic;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::AppError;
+use crate::settings::{LlmProviderKind, LlmSettings, Settings};
+
+pub trait LlmProvider {
+    /// Sends a single user message and returns the text of the reply.
+    fn prompt(&self, prompt: &str) -> impl Future<Output = Result<String, AppError>> + Send;
+}
+
+/// A reply in the format requested by a prompt.
+pub trait LlmResponse: Sized {
+    /// Example of the expected format, shown to the LLM when its reply can't be parsed.
+    const FORMAT: &'static str;
+
+    fn parse(response: &str) -> Result<Self, AppError>;
+}
+
+/// How many times to ask the LLM to fix a reply that can't be parsed.
+const MAX_REPAIR_ATTEMPTS: usize = 1;
+
+static REPAIR_PROMPT: &str = "
+I asked you this:
+
+<request>
+{prompt}
+</request>
+
+But your reply could not be parsed: {error}
+
+<reply>
+{response}
+</reply>
+
+Reply again with the same content, strictly in this format:
+
+{format}
+";
+
+/// Prompts the LLM and parses the reply, asking the LLM to fix it if it's malformed.
+pub async fn prompt_parsed<T: LlmResponse>(
+    llm: &impl LlmProvider,
+    prompt: &str,
+) -> Result<T, AppError> {
+    let mut response = llm.prompt(prompt).await?;
+    let mut attempt = 0;
+    loop {
+        match T::parse(&response) {
+            Ok(parsed) => return Ok(parsed),
+            Err(e) if attempt >= MAX_REPAIR_ATTEMPTS => return Err(e),
+            Err(e) => {
+                log::warn!("Failed to parse the LLM response, retrying: {}", e);
+                let repair_prompt = REPAIR_PROMPT
+                    .replace("{prompt}", prompt)
+                    .replace("{error}", &e.to_string())
+                    .replace("{response}", &response)
+                    .replace("{format}", T::FORMAT);
+                response = llm.prompt(&repair_prompt).await?;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Content of the first `<tag>...</tag>` in a non-perfect LLM output.
+pub fn parse_xml_tag(text: &str, tag: &str) -> Option<String> {
+    let start_tag = format!("<{}>", tag);
+    let end_tag = format!("</{}>", tag);
+
+    let content_start = text.find(&start_tag)? + start_tag.len();
+    let content_len = text[content_start..].find(&end_tag)?;
+    Some(
+        text[content_start..content_start + content_len]
+            .trim()
+            .to_string(),
+    )
+}
+
+/// Contents of all the `<tag>...</tag>` in the text, e.g. when the LLM lists multiple items.
+pub fn parse_xml_tags(text: &str, tag: &str) -> Vec<String> {
+    let start_tag = format!("<{}>", tag);
+    let end_tag = format!("</{}>", tag);
+
+    let mut contents = Vec::new();
+    let mut rest = text;
+    while let Some(start_idx) = rest.find(&start_tag) {
+        let content_start = start_idx + start_tag.len();
+        let Some(content_len) = rest[content_start..].find(&end_tag) else {
+            break;
+        };
+        contents.push(
+            rest[content_start..content_start + content_len]
+                .trim()
+                .to_string(),
+        );
+        rest = &rest[content_start + content_len + end_tag.len()..];
+    }
+    contents
+}
+
+/// Same as `parse_xml_tag()`, but a missing or empty tag is an error.
+pub fn require_xml_tag(text: &str, tag: &str) -> Result<String, AppError> {
+    match parse_xml_tag(text, tag) {
+        Some(content) if !content.is_empty() => Ok(content),
+        Some(_) => Err(AppError::LlmError(format!("<{}> is empty", tag))),
+        None => Err(AppError::LlmError(format!("<{}> is missing", tag))),
+    }
+}
+
+/// One of the supported providers, picked at runtime from the settings.
+pub enum Provider {
+    Anthropic(Box<AnthropicProvider>),
+    OpenAiCompatible(OpenAiCompatibleProvider),
+    #[cfg(test)]
+    Mock(MockProvider),
+}
+
+impl Provider {
+    pub fn from_settings() -> Self {
+        let settings = Settings::get();
+        match settings.llm.provider {
+            LlmProviderKind::Anthropic => Provider::Anthropic(Box::new(AnthropicProvider::new(
+                &settings.anthropic_api_key,
+                &settings.llm,
+            ))),
+            LlmProviderKind::OpenAi => {
+                Provider::OpenAiCompatible(OpenAiCompatibleProvider::new(&settings.llm))
+            }
+        }
+    }
+}
+
+impl LlmProvider for Provider {
+    async fn prompt(&self, prompt: &str) -> Result<String, AppError> {
+        match self {
+            Provider::Anthropic(provider) => provider.prompt(prompt).await,
+            Provider::OpenAiCompatible(provider) => provider.prompt(prompt).await,
+            #[cfg(test)]
+            Provider::Mock(provider) => provider.prompt(prompt).await,
+        }
+    }
+}
+
+pub struct AnthropicProvider {
+    agent: rig::agent::Agent<anthropic::completion::CompletionModel>,
+}
+
+impl AnthropicProvider {
+    pub fn new(api_key: &str, settings: &LlmSettings) -> Self {
+        let mut agent = anthropic::Client::new(api_key)
+            .agent(&settings.model)
+            .max_tokens(settings.max_tokens);
+        if let Some(temperature) = settings.temperature {
+            agent = agent.temperature(temperature);
+        }
+        Self {
+            agent: agent.build(),
+        }
+    }
+}
+
+impl LlmProvider for AnthropicProvider {
+    async fn prompt(&self, prompt: &str) -> Result<String, AppError> {
+        Ok(self.agent.prompt(prompt).await?)
+    }
+}
+
+/// Talks to the `/chat/completions` endpoint, which most local servers (llama.cpp, Ollama, vLLM)
+/// implement.
+pub struct OpenAiCompatibleProvider {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: String,
+    model: String,
+    max_tokens: u64,
+    temperature: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    max_tokens: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f64>,
+    messages: Vec<ChatMessage<'a>>,
+}
+
+#[derive(Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatResponseMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatResponseMessage {
+    content: String,
+}
+
+impl OpenAiCompatibleProvider {
+    pub fn new(settings: &LlmSettings) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: settings.base_url.trim_end_matches('/').to_string(),
+            api_key: settings.api_key.clone(),
+            model: settings.model.clone(),
+            max_tokens: settings.max_tokens,
+            temperature: settings.temperature,
+        }
+    }
+}
+
+impl LlmProvider for OpenAiCompatibleProvider {
+    async fn prompt(&self, prompt: &str) -> Result<String, AppError> {
+        let request = ChatRequest {
+            model: &self.model,
+            max_tokens: self.max_tokens,
+            temperature: self.temperature,
+            messages: vec![ChatMessage {
+                role: "user",
+                content: prompt,
+            }],
+        };
+        let mut http_request = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .json(&request);
+        if !self.api_key.is_empty() {
+            http_request = http_request.bearer_auth(&self.api_key);
+        }
+
+        let response: ChatResponse = http_request
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        response
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.content)
+            .ok_or_else(|| AppError::LlmError("The response has no choices".to_string()))
+    }
+}
+
+/// Replies with the given responses in order and remembers the prompts.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MockProvider {
+    responses: Mutex<VecDeque<String>>,
+    prompts: Mutex<Vec<String>>,
+}
+
+#[cfg(test)]
+impl MockProvider {
+    pub fn new<S: Into<String>>(responses: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            responses: Mutex::new(responses.into_iter().map(Into::into).collect()),
+            prompts: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The prompts received so far.
+    pub fn prompts(&self) -> Vec<String> {
+        self.prompts.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl LlmProvider for MockProvider {
+    async fn prompt(&self, prompt: &str) -> Result<String, AppError> {
+        self.prompts.lock().unwrap().push(prompt.to_string());
+        self.responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .ok_or_else(|| AppError::LlmError("No more mock responses".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Answer(String);
+
+    impl LlmResponse for Answer {
+        const FORMAT: &'static str = "<answer>...</answer>";
+
+        fn parse(response: &str) -> Result<Self, AppError> {
+            Ok(Answer(require_xml_tag(response, "answer")?))
+        }
+    }
+
+    #[test]
+    fn test_parse_xml_tag() {
+        assert_eq!(parse_xml_tag("a <b> c </b>", "b"), Some("c".to_string()));
+        assert_eq!(parse_xml_tag("</b> <b>c</b>", "b"), Some("c".to_string()));
+        assert_eq!(parse_xml_tag("<b>c", "b"), None);
+        assert_eq!(parse_xml_tag("", "b"), None);
+    }
+
+    #[test]
+    fn test_parse_xml_tags() {
+        assert_eq!(
+            parse_xml_tags("<b>1</b> <b>2</b> <b>3", "b"),
+            vec!["1", "2"]
+        );
+        assert!(parse_xml_tags("</b>", "b").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_prompt_parsed_repairs_malformed_response() {
+        let mock = MockProvider::new(["The answer is 42", "<answer>42</answer>"]);
+
+        let Answer(answer) = prompt_parsed(&mock, "question").await.unwrap();
+
+        assert_eq!(answer, "42");
+        let prompts = mock.prompts();
+        assert_eq!(prompts.len(), 2);
+        assert!(prompts[1].contains("<answer> is missing"));
+        assert!(prompts[1].contains("The answer is 42"));
+    }
+
+    #[tokio::test]
+    async fn test_prompt_parsed_gives_up() {
+        let mock = MockProvider::new(["42", "<answer></answer>", "<answer>42</answer>"]);
+
+        let result = prompt_parsed::<Answer>(&mock, "question").await;
+
+        assert!(matches!(result, Err(AppError::LlmError(e)) if e == "<answer> is empty"));
+    }
+
+    #[tokio::test]
+    async fn test_mock_provider_replies_in_order() {
+        let provider = Provider::Mock(MockProvider::new(["first", "second"]));
+
+        assert_eq!(provider.prompt("a").await.unwrap(), "first");
+        assert_eq!(provider.prompt("b").await.unwrap(), "second");
+        assert!(provider.prompt("c").await.is_err());
+
+        let Provider::Mock(mock) = provider else {
+            unreachable!()
+        };
+        assert_eq!(mock.prompts(), vec!["a", "b", "c"]);
+    }
+}
@@ -2,6 +2,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct Flashcard {
@@ -42,9 +43,115 @@ pub enum FlashcardAnswer {
     Not,
 }
 
+/// How well the answer was remembered.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Grade {
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+impl Grade {
+    pub const ALL: [Grade; 4] = [Grade::Again, Grade::Hard, Grade::Good, Grade::Easy];
+
+    pub fn remembered(&self) -> bool {
+        *self != Grade::Again
+    }
+
+    /// The next review interval given the current one.
+    pub fn next_review_after_secs(&self, review_after_secs: i64) -> i64 {
+        match self {
+            // Don't prompt to review immediately.
+            // Review no earlier than after 6 hours.
+            Grade::Again => 21600,
+            Grade::Hard => review_after_secs.max(21600),
+            Grade::Good => review_after_secs * 2,
+            Grade::Easy => review_after_secs * 3,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Grade::Again => "again",
+            Grade::Hard => "hard",
+            Grade::Good => "good",
+            Grade::Easy => "easy",
+        }
+    }
+}
+
+impl FromStr for Grade {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Grade::ALL
+            .into_iter()
+            .find(|grade| grade.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("Unknown grade: {}", s))
+    }
+}
+
+impl From<FlashcardAnswer> for Grade {
+    fn from(answer: FlashcardAnswer) -> Self {
+        match answer {
+            FlashcardAnswer::Remember => Grade::Good,
+            FlashcardAnswer::Not => Grade::Again,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ReviewHistory {
     pub flashcard_id: i64,
     pub review_date: DateTime<Utc>,
     pub remembered: bool,
+    /// Missing for the reviews done before grades were introduced.
+    pub grade: Option<Grade>,
+    pub feedback: Option<String>,
+}
+
+/// A grade of a typed answer, given by an LLM.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct AnswerGrade {
+    pub grade: Grade,
+    /// What was missing or wrong in the answer.
+    pub explanation: String,
+}
+
+/// A previous version of the flashcard content, stored whenever the card is edited.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct CardRevision {
+    pub id: i64,
+    pub flashcard_id: i64,
+    pub question: String,
+    pub answer: String,
+    pub examples: Option<String>,
+    /// When this version was replaced by an edit.
+    pub revised_at: DateTime<Utc>,
+}
+
+/// Two cards with similar questions.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DuplicatePair {
+    pub card: Flashcard,
+    pub duplicate: Flashcard,
+    /// From 0.0 to 1.0, where 1.0 means the same question.
+    pub similarity: f64,
+}
+
+/// Improvements of the card content suggested by an LLM.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct CardSuggestion {
+    pub question: String,
+    pub answer: String,
+    pub examples: String,
+}
+
+/// Answer options of a card reviewed as a multiple-choice question.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct ChoiceSet {
+    pub card_id: i64,
+    /// The right answer among the wrong ones.
+    pub options: Vec<String>,
 }
@@ -12,30 +12,71 @@ use std::fmt::{self, Display};
 use std::fs;
 #[cfg(feature = "ssr")]
 use std::path::Path;
+use std::str::FromStr;
 use std::sync::OnceLock;
 
-/// Supported languages.
-#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy)]
-pub enum Language {
-    #[default]
-    #[serde(rename = "spanish")]
-    Spanish,
-    #[serde(rename = "french")]
-    French,
-    #[serde(rename = "portuguese")]
-    Portuguese,
-    #[serde(rename = "german")]
-    German,
-}
+use crate::errors::AppError;
+
+/// A language, identified by its name, e.g. "spanish".
+/// The details are in the `LanguageInfo` with the same name.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct Language(String);
 
 impl Language {
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            Language::Spanish => "spanish",
-            Language::French => "french",
-            Language::Portuguese => "portuguese",
-            Language::German => "german",
+    /// Doesn't check the name, which ends up in file names: use `parse` for user input.
+    pub(crate) fn new(name: &str) -> Self {
+        Self(name.trim().to_lowercase())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Details of the language from the built-in table or from settings.toml.
+    pub fn info(&self) -> Result<LanguageInfo, AppError> {
+        all_languages()
+            .into_iter()
+            .find(|info| &info.name == self)
+            .ok_or_else(|| AppError::UnsupportedLanguage(self.to_string()))
+    }
+}
+
+impl Default for Language {
+    fn default() -> Self {
+        Self::new("spanish")
+    }
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lang = Self::new(s);
+        // The name is used as a file name, so it must not be a path.
+        if lang.0.is_empty()
+            || !lang
+                .0
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!("Invalid language name: {:?}", s));
         }
+        Ok(lang)
+    }
+}
+
+impl TryFrom<String> for Language {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Language> for String {
+    fn from(lang: Language) -> Self {
+        lang.0
     }
 }
 
@@ -45,11 +86,179 @@ impl Display for Language {
     }
 }
 
+/// Everything that differs between languages. Adding a language only needs a new entry.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LanguageInfo {
+    /// Used in settings, tags and the words database file name.
+    pub name: Language,
+    pub display_name: String,
+    /// ISO 639-1 code, e.g. "es".
+    pub iso_code: String,
+    /// Code used by the translator, when it differs from the ISO code, e.g. "pt-PT".
+    #[serde(default)]
+    pub translator_code: Option<String>,
+    /// Left out of the words to learn.
+    #[serde(default)]
+    pub articles: Vec<String>,
+    /// Common words that are left out of the words to learn, just like articles.
+    #[serde(default)]
+    pub stop_words: Vec<String>,
+    /// Text-to-speech voice, e.g. "es-ES".
+    #[serde(default)]
+    pub tts_voice: Option<String>,
+    /// Snowball stemmer used to match inflected words, e.g. "spanish".
+    #[serde(default)]
+    pub stemmer: Option<String>,
+    /// Grammatical persons in the order of conjugation tables, e.g. "yo", "tú"...
+    #[serde(default)]
+    pub persons: Vec<String>,
+    /// Tenses for conjugation drills, named in the language.
+    #[serde(default)]
+    pub tenses: Vec<String>,
+}
+
+impl LanguageInfo {
+    pub fn translator_code(&self) -> &str {
+        self.translator_code.as_deref().unwrap_or(&self.iso_code)
+    }
+
+    /// Articles and stop-words are not worth learning on their own.
+    pub fn is_skipped_word(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        self.articles.contains(&word) || self.stop_words.contains(&word)
+    }
+}
+
+#[derive(Deserialize)]
+struct BuiltinLanguages {
+    languages: Vec<LanguageInfo>,
+}
+
+fn builtin_languages() -> &'static [LanguageInfo] {
+    static BUILTIN: OnceLock<Vec<LanguageInfo>> = OnceLock::new();
+    BUILTIN.get_or_init(|| {
+        toml::from_str::<BuiltinLanguages>(include_str!("languages/languages.toml"))
+            .expect("Built-in languages table is invalid")
+            .languages
+    })
+}
+
+/// Built-in languages, with the ones from settings.toml added or replacing those with the same name.
+pub fn all_languages() -> Vec<LanguageInfo> {
+    let mut languages = builtin_languages().to_vec();
+    for custom in SETTINGS
+        .get()
+        .map(|s| s.languages.as_slice())
+        .unwrap_or_default()
+    {
+        match languages.iter_mut().find(|info| info.name == custom.name) {
+            Some(info) => *info = custom.clone(),
+            None => languages.push(custom.clone()),
+        }
+    }
+    languages
+}
+
+/// Where the LLM requests are sent.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+pub enum LlmProviderKind {
+    #[default]
+    #[serde(rename = "anthropic")]
+    Anthropic,
+    /// Any server implementing the OpenAI chat completions API, e.g. llama.cpp or Ollama.
+    #[serde(rename = "openai")]
+    OpenAi,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct LlmSettings {
+    pub provider: LlmProviderKind,
+    pub model: String,
+    pub max_tokens: u64,
+    /// The default of the provider when not set.
+    pub temperature: Option<f64>,
+    /// Only used by the OpenAI compatible provider.
+    pub base_url: String,
+    /// Only used by the OpenAI compatible provider. Local servers usually don't need it.
+    pub api_key: String,
+}
+
+impl Default for LlmSettings {
+    fn default() -> Self {
+        Self {
+            provider: LlmProviderKind::Anthropic,
+            model: "claude-sonnet-4-0".to_string(),
+            max_tokens: 1000,
+            temperature: None,
+            base_url: "http://localhost:11434/v1".to_string(),
+            api_key: "".to_string(),
+        }
+    }
+}
+
+/// Used for translations that are not in the vocabulary, the dictionary or the cache.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+pub enum OnlineTranslatorKind {
+    #[default]
+    #[serde(rename = "google")]
+    Google,
+    #[serde(rename = "llm")]
+    Llm,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct TranslationSettings {
+    /// Only the vocabulary, the dictionary and the cache are used.
+    pub offline: bool,
+    pub online: OnlineTranslatorKind,
+    /// ISO code of the language to translate into.
+    pub target_language: String,
+}
+
+impl Default for TranslationSettings {
+    fn default() -> Self {
+        Self {
+            offline: false,
+            online: OnlineTranslatorKind::Google,
+            target_language: "en".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct StorySettings {
+    /// A story is simplified when I know less than this share of its words, from 0.0 to 1.0.
+    pub min_coverage: f64,
+    /// How many times a story is generated or simplified at most.
+    pub max_attempts: usize,
+}
+
+impl Default for StorySettings {
+    fn default() -> Self {
+        Self {
+            min_coverage: 0.95,
+            max_attempts: 3,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Settings {
     pub anthropic_api_key: String,
     pub learning_language: Language,
     pub db_path: String,
+    #[serde(default)]
+    pub llm: LlmSettings,
+    #[serde(default)]
+    pub translation: TranslationSettings,
+    #[serde(default)]
+    pub stories: StorySettings,
+    /// Added to the built-in languages, see `LanguageInfo`.
+    #[serde(default)]
+    pub languages: Vec<LanguageInfo>,
 }
 
 #[cfg(feature = "ssr")]
@@ -57,13 +266,17 @@ impl Default for Settings {
     fn default() -> Self {
         Self {
             anthropic_api_key: "".to_string(),
-            learning_language: Language::Spanish,
+            learning_language: Language::default(),
             db_path: dirs::home_dir()
                 .unwrap()
                 .join("flashcards")
                 .to_str()
                 .unwrap()
                 .to_string(),
+            llm: LlmSettings::default(),
+            translation: TranslationSettings::default(),
+            stories: StorySettings::default(),
+            languages: Vec::new(),
         }
     }
 }
@@ -85,6 +298,12 @@ impl Settings {
     /// Call this from main.rs.
     #[cfg(feature = "ssr")]
     pub fn load() -> Result<Self, config::ConfigError> {
+        Self::load_with(|_| {})
+    }
+
+    /// Same as `load()`, but the loaded settings can be overridden, e.g. by command line arguments.
+    #[cfg(feature = "ssr")]
+    pub fn load_with(overrides: impl FnOnce(&mut Settings)) -> Result<Self, config::ConfigError> {
         let cfg_dir = dirs::config_dir()
             .ok_or(config::ConfigError::NotFound("config_dir".to_string()))?
             .join("flashcard-app");
@@ -123,8 +342,14 @@ impl Settings {
 
         let cfg = cfg.add_source(config::File::with_name(settings_path.to_str().unwrap()));
 
-        let settings = cfg.build()?.try_deserialize::<Settings>()?;
+        let mut settings = cfg.build()?.try_deserialize::<Settings>()?;
+        overrides(&mut settings);
         SETTINGS.set(settings.clone()).unwrap();
+        // Checked once the settings are set, so that the languages from settings.toml are known.
+        settings
+            .learning_language
+            .info()
+            .map_err(|e| config::ConfigError::Message(e.to_string()))?;
         Ok(settings)
     }
 
@@ -140,3 +365,31 @@ impl Settings {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_languages() {
+        let portuguese = Language::new("Portuguese").info().unwrap();
+        assert_eq!(portuguese.iso_code, "pt");
+        assert_eq!(portuguese.translator_code(), "pt-PT");
+        assert_eq!(
+            Language::new("german").info().unwrap().translator_code(),
+            "de"
+        );
+        assert!(Language::new("spanish")
+            .info()
+            .unwrap()
+            .is_skipped_word("Los"));
+        assert!(Language::new("klingon").info().is_err());
+    }
+
+    #[test]
+    fn test_language_name_is_not_a_path() {
+        assert!("lithuanian".parse::<Language>().is_ok());
+        assert!("../spanish".parse::<Language>().is_err());
+        assert!(Language::try_from(String::new()).is_err());
+    }
+}
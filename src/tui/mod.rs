@@ -0,0 +1,9 @@
+//! Terminal UI for the `cli` binary.
+
+#![cfg(feature = "ssr")]
+
+mod review;
+mod text;
+
+pub use review::{review, Summary};
+pub use text::rich_text_lines;
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use super::text::rich_text_lines;
+use crate::db::Database;
+use crate::languages::progress::record_card_review;
+use crate::model::{Flashcard, Grade};
+
+/// What was done during a review session.
+pub struct Summary {
+    pub grades: HashMap<Grade, usize>,
+    pub duration: Duration,
+    /// Cards that were due, but not reviewed.
+    pub remaining: usize,
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reviewed: usize = self.grades.values().sum();
+        let secs = self.duration.as_secs();
+        writeln!(
+            f,
+            "Reviewed {} cards in {}m {}s",
+            reviewed,
+            secs / 60,
+            secs % 60
+        )?;
+        let grades = Grade::ALL
+            .iter()
+            .map(|grade| {
+                format!(
+                    "{}: {}",
+                    grade.as_str(),
+                    self.grades.get(grade).unwrap_or(&0)
+                )
+            })
+            .collect::<Vec<_>>();
+        writeln!(f, "  {}", grades.join(", "))?;
+        let remembered: usize = self
+            .grades
+            .iter()
+            .filter(|(grade, _)| grade.remembered())
+            .map(|(_, count)| count)
+            .sum();
+        if let Some(percent) = (remembered * 100).checked_div(reviewed) {
+            writeln!(f, "  Remembered: {}%", percent)?;
+        }
+        write!(f, "  Left for later: {}", self.remaining)
+    }
+}
+
+/// Reviews the cards in the terminal until all of them are graded or the user quits.
+pub fn review(
+    db: &Database,
+    cards: Vec<Flashcard>,
+    media_dir: &Path,
+) -> Result<Summary, Box<dyn Error>> {
+    let mut terminal = ratatui::init();
+    let result = ReviewSession::new(cards, media_dir).run(&mut terminal, db);
+    ratatui::restore();
+    result
+}
+
+struct ReviewSession {
+    cards: Vec<Flashcard>,
+    current: usize,
+    show_answer: bool,
+    show_examples: bool,
+    /// Reviewed cards as they were before the review, so that it can be undone.
+    reviewed: Vec<(Flashcard, Grade)>,
+    started_at: Instant,
+    media_dir: PathBuf,
+}
+
+impl ReviewSession {
+    fn new(cards: Vec<Flashcard>, media_dir: &Path) -> Self {
+        Self {
+            cards,
+            current: 0,
+            show_answer: false,
+            show_examples: false,
+            reviewed: Vec::new(),
+            started_at: Instant::now(),
+            media_dir: media_dir.to_path_buf(),
+        }
+    }
+
+    fn run(
+        mut self,
+        terminal: &mut DefaultTerminal,
+        db: &Database,
+    ) -> Result<Summary, Box<dyn Error>> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => break,
+                KeyCode::Char('u') => self.undo(db)?,
+                KeyCode::Char('e') => self.show_examples = !self.show_examples,
+                KeyCode::Char(' ') | KeyCode::Enter => self.show_answer = true,
+                KeyCode::Char(c @ '1'..='4') if self.show_answer => {
+                    let grade = Grade::ALL[c as usize - '1' as usize];
+                    self.grade(db, grade)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(self.summary())
+    }
+
+    fn grade(&mut self, db: &Database, grade: Grade) -> Result<(), Box<dyn Error>> {
+        if let Some(card) = self.cards.get(self.current) {
+            db.review(card.id, grade)?;
+            record_card_review(card, grade.remembered());
+            self.reviewed.push((card.clone(), grade));
+            self.current += 1;
+            self.show_answer = false;
+            self.show_examples = false;
+        }
+        Ok(())
+    }
+
+    fn undo(&mut self, db: &Database) -> Result<(), Box<dyn Error>> {
+        if let Some((card, _)) = self.reviewed.pop() {
+            db.undo_review(&card)?;
+            self.current -= 1;
+            self.show_answer = false;
+            self.show_examples = false;
+        }
+        Ok(())
+    }
+
+    fn summary(&self) -> Summary {
+        let mut grades = HashMap::new();
+        for (_, grade) in self.reviewed.iter() {
+            *grades.entry(*grade).or_default() += 1;
+        }
+        Summary {
+            grades,
+            duration: self.started_at.elapsed(),
+            remaining: self.cards.len() - self.current,
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let Some(card) = self.cards.get(self.current) else {
+            let summary = self.summary().to_string();
+            frame.render_widget(Line::from("All done!").bold(), header);
+            frame.render_widget(
+                Paragraph::new(summary).block(Block::bordered().title("Session summary")),
+                body,
+            );
+            frame.render_widget(Line::from("u: undo · q: quit").dim(), footer);
+            return;
+        };
+
+        frame.render_widget(
+            Line::from(format!(
+                "Card {}/{}  #{}",
+                self.current + 1,
+                self.cards.len(),
+                card.tags.join(", #")
+            ))
+            .bold(),
+            header,
+        );
+
+        let mut lines = rich_text_lines(&card.question);
+        lines.extend(self.image_line(&card.question_img));
+        if self.show_answer {
+            lines.push(Line::from("─".repeat(body.width.saturating_sub(2) as usize)).dim());
+            lines.extend(rich_text_lines(&card.answer));
+            lines.extend(self.image_line(&card.img));
+        }
+        if self.show_examples {
+            lines.push(Line::default());
+            lines.push(Line::styled(
+                "Examples:",
+                Style::new().add_modifier(Modifier::BOLD),
+            ));
+            lines.extend(rich_text_lines(
+                card.examples.as_deref().unwrap_or_default(),
+            ));
+        }
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered()),
+            body,
+        );
+
+        let help = if self.show_answer {
+            "1: again · 2: hard · 3: good · 4: easy · e: examples · u: undo · q: quit"
+        } else {
+            "space: show answer · e: examples · u: undo · q: quit"
+        };
+        frame.render_widget(Line::from(help).dim(), footer);
+    }
+
+    fn image_line(&self, img: &Option<String>) -> Option<Line<'static>> {
+        img.as_ref().map(|img| {
+            Line::from(format!("file://{}", self.media_dir.join(img).display())).underlined()
+        })
+    }
+}
@@ -0,0 +1,219 @@
+//! Renders rich text as styled terminal lines.
+//!
+//! Math and diagrams can't be drawn in a terminal, so they are shown as code:
+//! AsciiMath is meant to be readable as is anyway.
+//! Cloze deletions can't be clicked to reveal them, so they are shown revealed.
+
+use comrak::nodes::{AstNode, ListType, NodeValue};
+use comrak::{parse_document, Arena, Options};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+
+use crate::components::{parse_rich_text, TextBlock};
+
+const CODE_STYLE: Style = Style::new().fg(Color::Yellow);
+
+pub fn rich_text_lines(text: &str) -> Vec<Line<'static>> {
+    markdown_lines(&rich_text_to_markdown(text))
+}
+
+fn rich_text_to_markdown(text: &str) -> String {
+    parse_rich_text(text)
+        .into_iter()
+        .map(|block| match block {
+            TextBlock::Raw(text) => text,
+            TextBlock::AsciiMath(math) => format!("`` {} ``", math),
+            TextBlock::Latex {
+                tex,
+                display: false,
+            } => format!("`` {} ``", tex),
+            TextBlock::Latex { tex, display: true } => format!("\n```\n{}\n```\n", tex),
+            TextBlock::Diagram { source, .. } => format!("\n```\n{}\n```\n", source.trim_end()),
+            TextBlock::Cloze { text, .. } => format!("**[{}]**", text),
+        })
+        .collect()
+}
+
+fn markdown_lines(markdown: &str) -> Vec<Line<'static>> {
+    let arena = Arena::new();
+    let root = parse_document(&arena, markdown, &Options::default());
+    let mut writer = LineWriter::default();
+    writer.render(root);
+    writer.flush();
+    while writer.lines.last().is_some_and(|line| line.width() == 0) {
+        writer.lines.pop();
+    }
+    writer.lines
+}
+
+#[derive(Default)]
+struct LineWriter {
+    lines: Vec<Line<'static>>,
+    spans: Vec<Span<'static>>,
+    styles: Vec<Style>,
+    /// Prepended to each line: indentation of list items and block quote markers.
+    prefixes: Vec<String>,
+    /// The list item marker for the next line.
+    bullet: Option<String>,
+    /// Type and the next item number of the nested lists.
+    lists: Vec<(ListType, usize)>,
+}
+
+impl LineWriter {
+    fn render<'a>(&mut self, node: &'a AstNode<'a>) {
+        let value = node.data.borrow().value.clone();
+        match value {
+            NodeValue::Paragraph => {
+                self.render_children(node);
+                self.flush();
+                self.block_end();
+            }
+            NodeValue::Heading(_) => {
+                self.render_styled(node, Style::new().add_modifier(Modifier::BOLD));
+                self.flush();
+                self.block_end();
+            }
+            NodeValue::Text(text) => self.push(&text),
+            NodeValue::Code(code) => self.push_styled(&code.literal, CODE_STYLE),
+            NodeValue::SoftBreak => self.push(" "),
+            NodeValue::LineBreak => self.flush(),
+            NodeValue::Emph => {
+                self.render_styled(node, Style::new().add_modifier(Modifier::ITALIC))
+            }
+            NodeValue::Strong => {
+                self.render_styled(node, Style::new().add_modifier(Modifier::BOLD))
+            }
+            NodeValue::Strikethrough => {
+                self.render_styled(node, Style::new().add_modifier(Modifier::CROSSED_OUT))
+            }
+            NodeValue::Link(_) => {
+                self.render_styled(node, Style::new().add_modifier(Modifier::UNDERLINED))
+            }
+            NodeValue::Image(link) => self.push(&format!("[image: {}]", link.url)),
+            NodeValue::CodeBlock(block) => {
+                for line in block.literal.lines() {
+                    self.push_styled(line, CODE_STYLE);
+                    self.flush();
+                }
+                self.block_end();
+            }
+            NodeValue::HtmlBlock(block) => {
+                for line in block.literal.lines() {
+                    self.push(line);
+                    self.flush();
+                }
+                self.block_end();
+            }
+            NodeValue::HtmlInline(html) => self.push(&html),
+            NodeValue::List(list) => {
+                self.lists.push((list.list_type, list.start));
+                self.render_children(node);
+                self.lists.pop();
+                self.block_end();
+            }
+            NodeValue::Item(_) => {
+                let bullet = match self.lists.last_mut() {
+                    Some((ListType::Ordered, number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                self.prefixes.push(" ".repeat(bullet.chars().count()));
+                self.bullet = Some(bullet);
+                self.render_children(node);
+                self.flush();
+                self.prefixes.pop();
+            }
+            NodeValue::BlockQuote => {
+                self.prefixes.push("│ ".to_string());
+                self.render_children(node);
+                self.prefixes.pop();
+            }
+            NodeValue::ThematicBreak => {
+                self.lines.push(Line::from("───"));
+                self.block_end();
+            }
+            _ => self.render_children(node),
+        }
+    }
+
+    fn render_children<'a>(&mut self, node: &'a AstNode<'a>) {
+        for child in node.children() {
+            self.render(child);
+        }
+    }
+
+    fn render_styled<'a>(&mut self, node: &'a AstNode<'a>, style: Style) {
+        self.styles.push(style);
+        self.render_children(node);
+        self.styles.pop();
+    }
+
+    fn push(&mut self, text: &str) {
+        let style = self
+            .styles
+            .iter()
+            .fold(Style::default(), |acc, style| acc.patch(*style));
+        self.push_styled(text, style);
+    }
+
+    fn push_styled(&mut self, text: &str, style: Style) {
+        self.spans.push(Span::styled(text.to_string(), style));
+    }
+
+    /// Ends the current line.
+    fn flush(&mut self) {
+        if self.spans.is_empty() && self.bullet.is_none() {
+            return;
+        }
+
+        let prefix = match self.bullet.take() {
+            Some(bullet) => {
+                let outer = &self.prefixes[..self.prefixes.len().saturating_sub(1)];
+                format!("{}{}", outer.concat(), bullet)
+            }
+            None => self.prefixes.concat(),
+        };
+        let mut spans = vec![Span::raw(prefix)];
+        spans.append(&mut self.spans);
+        self.lines.push(Line::from(spans));
+    }
+
+    /// Separates top level blocks with an empty line. List items are kept tight.
+    fn block_end(&mut self) {
+        if self.lists.is_empty() && self.lines.last().is_some_and(|line| line.width() > 0) {
+            self.lines.push(Line::default());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain_text(lines: Vec<Line>) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_rich_text_lines() {
+        let lines = rich_text_lines(
+            "# Title\n\nWhat is `math 1/3` in *decimal*?\n\n* one\n* two\n\n1. first\n2. second",
+        );
+        assert_eq!(
+            plain_text(lines),
+            vec![
+                "Title",
+                "",
+                "What is 1/3 in decimal?",
+                "",
+                "• one",
+                "• two",
+                "",
+                "1. first",
+                "2. second",
+            ]
+        );
+    }
+}